@@ -38,36 +38,26 @@ impl NoGravity {
     pub fn create(x: i8, vx: i8, y: i8, vy: i8) -> BoxEntity {
         let (actor, driver) = GhostActor::new(NoGravityInner { x, vx, y, vy });
         tokio::task::spawn(driver);
-        let out = Self(actor.clone());
-
-        // spawn our task for handling this entity's movement
-        tokio::task::spawn(async move {
-            while actor.is_active() {
-                actor
-                    .invoke(move |inner| {
-                        inner.x += inner.vx;
-                        inner.y += inner.vy;
-                        if inner.x >= 16 {
-                            inner.vx = -1;
-                        }
-                        if inner.y >= 8 {
-                            inner.vy = -1;
-                        }
-                        if inner.x <= 1 {
-                            inner.vx = 1;
-                        }
-                        if inner.y <= 1 {
-                            inner.vy = 1;
-                        }
-                        Result::Ok(())
-                    })
-                    .await?;
-
-                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // tick this entity's movement
+        actor.invoke_every(std::time::Duration::from_millis(50), |inner| {
+            inner.x += inner.vx;
+            inner.y += inner.vy;
+            if inner.x >= 16 {
+                inner.vx = -1;
+            }
+            if inner.y >= 8 {
+                inner.vy = -1;
+            }
+            if inner.x <= 1 {
+                inner.vx = 1;
+            }
+            if inner.y <= 1 {
+                inner.vy = 1;
             }
-            Result::Ok(())
         });
-        Box::new(out)
+
+        Box::new(Self(actor))
     }
 }
 
@@ -103,43 +93,34 @@ impl Gravity {
         const G: f32 = 0.1;
         let (actor, driver) = GhostActor::new(GravityInner { x, vx, y, vy });
         tokio::task::spawn(driver);
-        let out = Self(actor.clone());
-        tokio::task::spawn(async move {
-            while actor.is_active() {
-                actor
-                    .invoke(move |inner| {
-                        inner.vy += G;
-                        inner.x += inner.vx;
-                        inner.y += inner.vy;
-                        if inner.x >= 16.0 {
-                            inner.vx = -inner.vx;
-                            inner.x -= 16.0;
-                        }
-                        if inner.y >= 8.0 {
-                            inner.vy = -inner.vy;
-                            inner.y -= 8.0;
-                            if inner.vy.abs() < 0.2 {
-                                inner.vy = -1.2;
-                            }
-                        }
-                        if inner.x <= 1.0 {
-                            inner.vx = -inner.vx;
-                            inner.x += 1.0 - inner.x;
-                        }
-                        if inner.y <= 1.0 {
-                            inner.vy = -inner.vy;
-                            inner.y += 1.0 - inner.y;
-                        }
-                        Result::Ok(())
-                    })
-                    .await?;
-
-                // target ~ 50 fps
-                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        // tick this entity's movement, target ~ 50 fps
+        actor.invoke_every(std::time::Duration::from_millis(20), |inner| {
+            inner.vy += G;
+            inner.x += inner.vx;
+            inner.y += inner.vy;
+            if inner.x >= 16.0 {
+                inner.vx = -inner.vx;
+                inner.x -= 16.0;
+            }
+            if inner.y >= 8.0 {
+                inner.vy = -inner.vy;
+                inner.y -= 8.0;
+                if inner.vy.abs() < 0.2 {
+                    inner.vy = -1.2;
+                }
+            }
+            if inner.x <= 1.0 {
+                inner.vx = -inner.vx;
+                inner.x += 1.0 - inner.x;
+            }
+            if inner.y <= 1.0 {
+                inner.vy = -inner.vy;
+                inner.y += 1.0 - inner.y;
             }
-            Result::Ok(())
         });
-        Box::new(out)
+
+        Box::new(Self(actor))
     }
 }
 
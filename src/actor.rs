@@ -1,15 +1,496 @@
+use crate::mailbox::{MailboxReceiver, MailboxSender, Queued};
 use crate::*;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tracing::Instrument;
 
-type InnerInvoke<T> = Box<dyn FnOnce(&mut T) + 'static + Send>;
-type SendInvoke<T> = futures::channel::mpsc::Sender<InnerInvoke<T>>;
+type SyncInvoke<T> = Box<dyn FnOnce(&mut T) + 'static + Send>;
+type ExclusiveInvoke<T> = Box<
+    dyn for<'a> FnOnce(&'a mut T) -> futures::future::BoxFuture<'a, ()>
+        + 'static
+        + Send,
+>;
+
+/// Evaluates a watch selector, returns `false` once nobody is watching.
+type Watcher<T> = Box<dyn FnMut(&T) -> bool + 'static + Send>;
+
+type ReadInvoke<T> = Box<dyn FnOnce(&T) + 'static + Send>;
+type RunReads<T> = fn(
+    &T,
+    Vec<ReadInvoke<T>>,
+    &DriveConfig,
+) -> Result<(), Box<dyn std::any::Any + Send>>;
+
+/// A unit of logic queued for processing by the actor driver.
+enum InnerInvoke<T> {
+    /// Synchronous logic given sequential access to the state.
+    Sync(SyncInvoke<T>),
+
+    /// Async logic that retains exclusive access to the state
+    /// until its future completes.
+    Exclusive(ExclusiveInvoke<T>),
+
+    /// Read-only logic, which may run concurrently with adjacent reads.
+    /// Carries the runner that knows `T: Sync`, since the driver does not.
+    Read(ReadInvoke<T>, RunReads<T>),
+}
+
+impl<T> InnerInvoke<T> {
+    fn exclusive<F>(f: F) -> Self
+    where
+        F: for<'a> FnOnce(&'a mut T) -> futures::future::BoxFuture<'a, ()>
+            + 'static
+            + Send,
+    {
+        Self::Exclusive(Box::new(f))
+    }
+}
+
+type SendInvoke<T> = MailboxSender<InnerInvoke<T>>;
+
+/// Delivers the result of an invocation back to its caller.
+/// If dropped without responding, the caller is informed why.
+struct Responder<R, E: From<GhostError>> {
+    o_send: Option<futures::channel::oneshot::Sender<Result<R, E>>>,
+    span: std::sync::Weak<tracing::Span>,
+    shared: Arc<Shared>,
+    /// only read the clock if the invoke span may be recorded
+    queued_at: Option<std::time::Instant>,
+    running: Option<(tracing::Span, std::time::Instant)>,
+}
+
+impl<R, E: From<GhostError>> Drop for Responder<R, E> {
+    fn drop(&mut self) {
+        if let Some(o_send) = self.o_send.take() {
+            let _ = o_send.send(Err(self.shared.dropped_error().into()));
+        }
+    }
+}
+
+impl<R, E> Responder<R, E>
+where
+    R: 'static + Send,
+    E: 'static + From<GhostError> + Send,
+{
+    /// Construct a responder capturing the current tracing context,
+    /// along with the future that resolves to its response.
+    fn new(
+        shared: Arc<Shared>,
+    ) -> (
+        Self,
+        impl std::future::Future<Output = Result<R, E>> + 'static + Send,
+    ) {
+        let strong = Arc::new(tracing::Span::current());
+        let span = Arc::downgrade(&strong);
+        let (o_send, o_recv) = futures::channel::oneshot::channel();
+        let responder = Self {
+            o_send: Some(o_send),
+            span,
+            shared: shared.clone(),
+            queued_at: match tracing::span_enabled!(tracing::Level::INFO) {
+                true => Some(std::time::Instant::now()),
+                false => None,
+            },
+            running: None,
+        };
+        let response = async move {
+            // keep the tracing context alive until we have a response
+            let _strong = strong;
+            match o_recv.await {
+                Ok(r) => r,
+                Err(e) => Err(shared.closed_error(e).into()),
+            }
+        };
+        (responder, response)
+    }
+
+    /// Returns `true` if the caller is no longer waiting on the result.
+    fn is_canceled(&self) -> bool {
+        self.o_send.as_ref().is_none_or(|o| o.is_canceled())
+    }
+
+    /// Mark the invocation as running, returning the span to run it in.
+    /// This is a child of the caller's tracing context, or that context
+    /// itself if the invoke span is disabled.
+    fn span(&mut self) -> tracing::Span {
+        let parent = self.span.upgrade().unwrap_or_else(|| {
+            tracing::warn!("TRACING: Parent context dropped");
+            Arc::new(tracing::Span::current())
+        });
+        let queued_at = match self.queued_at {
+            Some(queued_at) => queued_at,
+            None => return (*parent).clone(),
+        };
+        let now = std::time::Instant::now();
+        match self.shared.invoke_span(&parent, now - queued_at) {
+            Some(span) => {
+                self.running = Some((span.clone(), now));
+                span
+            }
+            None => (*parent).clone(),
+        }
+    }
+
+    fn respond(mut self, r: Result<R, E>) {
+        if let Some((span, started)) = self.running.take() {
+            span.record("exec_time", tracing::field::debug(started.elapsed()));
+        }
+        if let Some(o_send) = self.o_send.take() {
+            let _ = o_send.send(r);
+        }
+    }
+
+    /// Report a panic to the caller. The driver still needs to see the
+    /// panic to apply the configured policy, so resume unwinding after.
+    fn respond_panic(self, e: Box<dyn std::any::Any + Send>) -> ! {
+        let kind = GhostErrorKind::Panicked(panic_message(&*e));
+        self.respond(Err(GhostError::from(kind).into()));
+        std::panic::resume_unwind(e);
+    }
+
+    /// Run `invoke` in the invocation span and respond with its result,
+    /// or its panic, see `respond_panic()`. If `cancel_on_drop` is set
+    /// and the caller is no longer waiting, `invoke` is skipped.
+    fn run<F>(mut self, cancel_on_drop: bool, invoke: F)
+    where
+        F: FnOnce() -> Result<R, E>,
+    {
+        if cancel_on_drop && self.is_canceled() {
+            // nobody is waiting on the result, skip the logic
+            return;
+        }
+        let span = self.span();
+        span.in_scope(|| {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(invoke))
+            {
+                Ok(r) => self.respond(r),
+                Err(e) => self.respond_panic(e),
+            }
+        });
+    }
+
+    /// As `run()`, for async logic, which is not polled if skipped.
+    async fn run_async<F>(mut self, cancel_on_drop: bool, invoke: F)
+    where
+        F: std::future::Future<Output = Result<R, E>>,
+    {
+        if cancel_on_drop && self.is_canceled() {
+            // nobody is waiting on the result, skip the logic
+            return;
+        }
+        let span = self.span();
+        let res = futures::future::FutureExt::catch_unwind(
+            std::panic::AssertUnwindSafe(invoke),
+        )
+        .instrument(span)
+        .await;
+        match res {
+            Ok(r) => self.respond(r),
+            Err(e) => self.respond_panic(e),
+        }
+    }
+}
 
 /// GhostActor manages task efficient sequential mutable access
 /// to internal state data (type T).
 /// GhostActors are `'static` and cheaply clone-able.
 /// A clone retains a channel to the same internal state data.
-pub struct GhostActor<T: 'static + Send>(Arc<SendInvoke<T>>);
+pub struct GhostActor<T: 'static + Send> {
+    send: Arc<SendInvoke<T>>,
+    shared: Arc<Shared>,
+}
+
+/// Actor-wide data shared by all handles to the same actor.
+pub(crate) struct Shared {
+    name: Option<String>,
+    labels: Vec<(&'static str, &'static str)>,
+    invoke_timeout: Option<std::time::Duration>,
+    cancel_on_drop: bool,
+    poisoned: AtomicBool,
+    aborted: AtomicBool,
+    exited: ExitFuture,
+    metrics: Option<MetricsState>,
+    timer: Arc<dyn GhostTimer>,
+    events: events::EventBus,
+    spawned: std::sync::Mutex<Vec<futures::future::BoxFuture<'static, ()>>>,
+    spawned_waker: futures::task::AtomicWaker,
+    /// watchers not yet picked up by the driver, each a `Watcher<T>`
+    watching: std::sync::Mutex<Vec<Box<dyn std::any::Any + Send>>>,
+    exit_hooks: std::sync::Mutex<Option<Vec<ExitHook>>>,
+}
+
+/// Data owned by the driver, which outlives a supervised restart.
+struct DriveState<T> {
+    /// invocations pulled off the mailbox, but not yet processed
+    backlog: VecDeque<Queued<InnerInvoke<T>>>,
+    tasks: Tasks,
+    watchers: Vec<Watcher<T>>,
+    /// set if any invocations ran since watchers were last evaluated
+    dirty: bool,
+    processed: Option<ProcessedHook<T>>,
+}
+
+impl<T> DriveState<T> {
+    fn new() -> Self {
+        Self {
+            backlog: VecDeque::new(),
+            tasks: Tasks::new(),
+            watchers: Vec::new(),
+            dirty: false,
+            processed: None,
+        }
+    }
+}
+
+/// Returns `false` if the watcher should be dropped.
+fn eval_watcher<T>(t: &T, watcher: &mut Watcher<T>) -> bool {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| watcher(t)))
+    {
+        Ok(keep) => keep,
+        Err(e) => {
+            let msg = panic_message(&*e);
+            tracing::error!(%msg, "GhostActor watch selector panicked");
+            false
+        }
+    }
+}
+
+/// A panic caught on the driver, with its payload.
+type Panic = Box<dyn std::any::Any + Send>;
+
+/// Background tasks polled by the driver alongside the mailbox.
+/// Each resolves to the panic that ended it, if any.
+type Tasks = futures::stream::FuturesUnordered<
+    futures::future::BoxFuture<'static, Result<(), Panic>>,
+>;
+
+pub(crate) type ExitHook = Box<dyn FnOnce() + 'static + Send>;
+
+/// Held by the driver. When it completes or is dropped, runs the exit
+/// hooks, then resolves any `shutdown_graceful()` waiters.
+struct ExitGuard {
+    shared: Arc<Shared>,
+    _exit: futures::channel::oneshot::Sender<()>,
+}
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        let hooks = self.shared.exit_hooks.lock().unwrap().take();
+        // no more tasks can be spawned, drop any the driver never saw
+        drop(std::mem::take(&mut *self.shared.spawned.lock().unwrap()));
+        drop(std::mem::take(&mut *self.shared.watching.lock().unwrap()));
+        self.shared.events.close();
+        for hook in hooks.into_iter().flatten() {
+            hook();
+        }
+    }
+}
+
+/// Formats static labels as `key=value` pairs for tracing.
+struct Labels<'a>(&'a [(&'static str, &'static str)]);
+
+impl std::fmt::Display for Labels<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (k, v)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}={}", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolves once the driver has completed or been dropped.
+type ExitFuture =
+    futures::future::Shared<futures::channel::oneshot::Receiver<()>>;
+
+impl Shared {
+    fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Translate a lost connection to the actor task into a GhostError,
+    /// reporting poisoning or forced shutdown if that is why the actor
+    /// went away.
+    fn closed_error<E>(&self, e: E) -> GhostError
+    where
+        E: 'static + std::error::Error + Send + Sync,
+    {
+        if self.is_poisoned() {
+            GhostErrorKind::Poisoned.into()
+        } else if self.is_aborted() {
+            GhostErrorKind::Shutdown.into()
+        } else {
+            GhostError::other(e)
+        }
+    }
+
+    /// Run `hook` once the driver has completed or been dropped.
+    /// If that has already happened, `hook` is run immediately.
+    pub(crate) fn on_exit(&self, hook: ExitHook) {
+        let mut hooks = self.exit_hooks.lock().unwrap();
+        match hooks.as_mut() {
+            Some(hooks) => hooks.push(hook),
+            None => {
+                drop(hooks);
+                hook();
+            }
+        }
+    }
+
+    /// Have the driver poll `task` alongside the mailbox, until it
+    /// completes or the driver stops.
+    pub(crate) fn spawn(&self, task: futures::future::BoxFuture<'static, ()>) {
+        let exit_hooks = self.exit_hooks.lock().unwrap();
+        if exit_hooks.is_none() {
+            // the driver has already stopped
+            return;
+        }
+        self.spawned.lock().unwrap().push(task);
+        drop(exit_hooks);
+        self.spawned_waker.wake();
+    }
+
+    /// Have the driver evaluate `watcher`, a boxed `Watcher<T>`, after
+    /// each batch of invocations. If the driver has already stopped,
+    /// `watcher` is dropped.
+    fn watch(&self, watcher: Box<dyn std::any::Any + Send>) {
+        let exit_hooks = self.exit_hooks.lock().unwrap();
+        if exit_hooks.is_none() {
+            return;
+        }
+        self.watching.lock().unwrap().push(watcher);
+        drop(exit_hooks);
+        self.spawned_waker.wake();
+    }
+
+    /// Called by the driver to pick up newly registered watchers, which
+    /// see the current state straight away. Woken through `poll_tasks()`.
+    /// Takes `&mut T` only so the driver future needs no `T: Sync`.
+    fn take_watchers<T: 'static>(
+        &self,
+        t: &mut T,
+        watchers: &mut Vec<Watcher<T>>,
+    ) {
+        let watching = std::mem::take(&mut *self.watching.lock().unwrap());
+        for watcher in watching {
+            if let Ok(mut watcher) = watcher.downcast::<Watcher<T>>() {
+                if eval_watcher(t, &mut watcher) {
+                    watchers.push(*watcher);
+                }
+            }
+        }
+    }
+
+    /// Called by the driver to make progress on spawned tasks.
+    /// A task that panics is dropped, and its panic returned.
+    fn poll_tasks(
+        &self,
+        tasks: &mut Tasks,
+        cx: &mut std::task::Context<'_>,
+    ) -> Option<Panic> {
+        self.spawned_waker.register(cx.waker());
+        tasks.extend(self.spawned.lock().unwrap().drain(..).map(|task| {
+            // tasks poll user code (e.g. attached streams),
+            // which must not unwind the driver
+            futures::future::FutureExt::boxed(
+                futures::future::FutureExt::catch_unwind(
+                    std::panic::AssertUnwindSafe(task),
+                ),
+            )
+        }));
+        while let std::task::Poll::Ready(Some(res)) =
+            futures::stream::StreamExt::poll_next_unpin(tasks, cx)
+        {
+            res.err()?;
+        }
+        None
+    }
+
+    /// The span the driver runs in.
+    fn driver_span(&self) -> tracing::Span {
+        let span = tracing::info_span!(
+            "ghost_actor_driver",
+            actor = tracing::field::Empty,
+            labels = tracing::field::Empty,
+        );
+        self.record_identity(&span);
+        span
+    }
+
+    /// The span an invocation runs in, recording the actor identity
+    /// and timing, or `None` if this span is disabled.
+    fn invoke_span(
+        &self,
+        parent: &tracing::Span,
+        queue_wait: std::time::Duration,
+    ) -> Option<tracing::Span> {
+        let span = tracing::info_span!(
+            parent: parent,
+            "ghost_actor_invoke",
+            actor = tracing::field::Empty,
+            labels = tracing::field::Empty,
+            queue_wait = ?queue_wait,
+            exec_time = tracing::field::Empty,
+        );
+        if span.is_disabled() {
+            return None;
+        }
+        self.record_identity(&span);
+        Some(span)
+    }
+
+    fn record_identity(&self, span: &tracing::Span) {
+        if let Some(name) = &self.name {
+            span.record("actor", name.as_str());
+        }
+        if !self.labels.is_empty() {
+            span.record(
+                "labels",
+                tracing::field::display(Labels(&self.labels)),
+            );
+        }
+    }
+
+    /// Apply an optional deadline to an invocation result future.
+    fn with_timeout<R, E>(
+        &self,
+        timeout: Option<std::time::Duration>,
+        fut: GhostFuture<R, E>,
+    ) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+    {
+        match timeout {
+            Some(timeout) => {
+                let timer = self.timer.clone();
+                resp(
+                    async move { timer::timeout(&*timer, timeout, fut).await? },
+                )
+            }
+            None => fut,
+        }
+    }
+
+    /// The error reported to callers whose invocation was dropped
+    /// without ever being processed.
+    fn dropped_error(&self) -> GhostError {
+        if self.is_poisoned() {
+            GhostErrorKind::Poisoned.into()
+        } else if self.is_aborted() {
+            GhostErrorKind::Shutdown.into()
+        } else {
+            GhostErrorKind::Dropped.into()
+        }
+    }
+}
 
 impl<T: 'static + Send> GhostActor<T> {
     /// Create a new GhostActor with default config and initial state.
@@ -19,29 +500,255 @@ impl<T: 'static + Send> GhostActor<T> {
 
     /// Create a new GhostActor with config and initial state.
     pub fn new_config(config: GhostConfig, t: T) -> (Self, GhostDriver) {
+        let (actor, driver) = Self::new_hooks(config, t, Hooks::none());
+        (actor, driver.into())
+    }
+
+    /// Create a new GhostActor with config and initial state.
+    /// The returned driver resolves to the final state once the actor
+    /// has shut down and drained its queue.
+    pub fn new_config_state(
+        config: GhostConfig,
+        t: T,
+    ) -> (Self, GhostStateDriver<T>) {
+        Self::new_hooks(config, t, Hooks::none())
+    }
+
+    /// Create a new GhostActor with config and initial state,
+    /// invoking the `GhostLifecycle` hooks of the state when the driver
+    /// starts and stops.
+    pub fn new_lifecycle(config: GhostConfig, t: T) -> (Self, GhostDriver)
+    where
+        T: GhostLifecycle,
+    {
+        let (actor, driver) = Self::new_hooks(config, t, Hooks::lifecycle());
+        (actor, driver.into())
+    }
+
+    pub(crate) fn new_hooks(
+        config: GhostConfig,
+        t: T,
+        hooks: Hooks<T>,
+    ) -> (Self, GhostStateDriver<T>) {
         let mut t = t;
 
-        let (send, recv) = futures::channel::mpsc::channel::<InnerInvoke<T>>(
-            config.channel_bound,
-        );
+        let (actor, recv, exit) = Self::new_parts(&config);
+
+        let drive_config = DriveConfig::new(&config);
+        let shared = actor.shared.clone();
+        let driver_span = shared.driver_span();
+        let driver = GhostStateDriver(futures::future::FutureExt::boxed(
+            async move {
+                // resolve shutdown_graceful() waiters when we complete,
+                // declared first so it drops after everything else
+                let _exit = exit;
 
-        let driver =
-            GhostDriver(futures::future::FutureExt::boxed(async move {
                 // mitigate task thrashing
-                let mut recv =
-                    futures::stream::StreamExt::ready_chunks(recv, 1024);
+                let mut recv = futures::stream::StreamExt::ready_chunks(
+                    recv,
+                    drive_config.max_batch_size,
+                );
+                let Hooks {
+                    started,
+                    stopping,
+                    processed,
+                    stopped,
+                } = hooks;
+                let mut state = DriveState::new();
+                state.processed = processed;
+
+                let res = std::panic::catch_unwind(
+                    std::panic::AssertUnwindSafe(|| started(&mut t)),
+                );
+                if let Err(e) = res {
+                    if on_panic(e, "started hook", &shared, &drive_config) {
+                        // poison before the mailbox is dropped,
+                        // so callers are informed correctly
+                        shared.poisoned.store(true, Ordering::SeqCst);
+                        return Err(GhostErrorKind::Poisoned.into());
+                    }
+                }
 
-                while let Some(invokes) =
-                    futures::stream::StreamExt::next(&mut recv).await
+                match drive(
+                    &mut recv,
+                    &mut state,
+                    &mut t,
+                    &shared,
+                    &drive_config,
+                )
+                .await
                 {
-                    for invoke in invokes {
-                        // give invokes sequential access to mutable state
-                        invoke(&mut t);
+                    DriverExit::Closed => {
+                        let res = futures::future::FutureExt::catch_unwind(
+                            std::panic::AssertUnwindSafe(async {
+                                stopping(&mut t).await
+                            }),
+                        )
+                        .await;
+                        if let Err(e) = res {
+                            if on_panic(
+                                e,
+                                "stopping hook",
+                                &shared,
+                                &drive_config,
+                            ) {
+                                shared.poisoned.store(true, Ordering::SeqCst);
+                            }
+                            if shared.is_poisoned() {
+                                return Err(GhostErrorKind::Poisoned.into());
+                            }
+                        }
+                        if let Some(stopped) = stopped {
+                            if !shared.is_poisoned() {
+                                stopped(&t);
+                            }
+                        }
+                        Ok(t)
+                    }
+                    DriverExit::Panicked => {
+                        // poison before the backlog is dropped,
+                        // so those callers are informed correctly
+                        shared.poisoned.store(true, Ordering::SeqCst);
+                        Err(GhostErrorKind::Poisoned.into())
                     }
                 }
-            }));
+            }
+            .instrument(driver_span),
+        ));
+
+        (actor, driver)
+    }
+
+    /// Create a new supervised GhostActor with config.
+    /// The state is built by `factory`. If an invocation panics under
+    /// `GhostPanicPolicy::Shutdown`, the state is rebuilt by `factory` and
+    /// the actor keeps serving existing handles, unless the restart
+    /// policy has been exhausted, at which point the actor is poisoned.
+    pub fn new_supervised<F>(
+        config: GhostConfig,
+        restart_policy: GhostRestartPolicy,
+        factory: F,
+    ) -> (Self, GhostDriver)
+    where
+        F: FnMut() -> T + 'static + Send,
+    {
+        let mut factory = factory;
+
+        let (actor, recv, exit) = Self::new_parts(&config);
+
+        let drive_config = DriveConfig::new(&config);
+        let shared = actor.shared.clone();
+        let driver_span = shared.driver_span();
+        let driver = GhostDriver(futures::future::FutureExt::boxed(
+            async move {
+                // resolve shutdown_graceful() waiters when we complete,
+                // declared first so it drops after everything else
+                let _exit = exit;
+
+                // mitigate task thrashing
+                let mut recv = futures::stream::StreamExt::ready_chunks(
+                    recv,
+                    drive_config.max_batch_size,
+                );
+                let mut state = DriveState::new();
+                let mut restarts = VecDeque::new();
+
+                loop {
+                    let mut t = factory();
+
+                    if let DriverExit::Closed = drive(
+                        &mut recv,
+                        &mut state,
+                        &mut t,
+                        &shared,
+                        &drive_config,
+                    )
+                    .await
+                    {
+                        return;
+                    }
+
+                    // drop the (potentially inconsistent) state
+                    drop(t);
+
+                    let now = std::time::Instant::now();
+                    while restarts.front().is_some_and(|r| {
+                        now.duration_since(*r) > restart_policy.window
+                    }) {
+                        restarts.pop_front();
+                    }
+
+                    if restarts.len() >= restart_policy.max_restarts {
+                        tracing::error!(
+                            max_restarts = restart_policy.max_restarts,
+                            window = ?restart_policy.window,
+                            "GhostActor exceeded restart policy, giving up",
+                        );
+                        shared.poisoned.store(true, Ordering::SeqCst);
+                        return;
+                    }
+
+                    restarts.push_back(now);
+                    tracing::warn!(
+                        restart = restarts.len(),
+                        max_restarts = restart_policy.max_restarts,
+                        backoff = ?restart_policy.backoff,
+                        "GhostActor panicked, restarting",
+                    );
+
+                    if restart_policy.backoff > std::time::Duration::ZERO {
+                        shared.timer.sleep(restart_policy.backoff).await;
+                    }
+                }
+            }
+            .instrument(driver_span),
+        ));
+
+        (actor, driver)
+    }
 
-        (Self(Arc::new(send)), driver)
+    /// Construct a handle along with the receiving side of its channel,
+    /// and the guard the driver must drop when it completes.
+    fn new_parts(
+        config: &GhostConfig,
+    ) -> (Self, MailboxReceiver<InnerInvoke<T>>, ExitGuard) {
+        let (send, recv) = mailbox::mailbox(config);
+
+        let (exit, exited) = futures::channel::oneshot::channel();
+
+        let shared = Arc::new(Shared {
+            name: config.name.clone(),
+            labels: config.labels.clone(),
+            invoke_timeout: config.invoke_timeout,
+            cancel_on_drop: config.cancel_on_drop,
+            poisoned: AtomicBool::new(false),
+            aborted: AtomicBool::new(false),
+            exited: futures::future::FutureExt::shared(exited),
+            metrics: match config.metrics {
+                true => Some(MetricsState::default()),
+                false => None,
+            },
+            timer: config.timer.clone(),
+            events: events::EventBus::new(),
+            spawned: std::sync::Mutex::new(Vec::new()),
+            spawned_waker: futures::task::AtomicWaker::new(),
+            watching: std::sync::Mutex::new(Vec::new()),
+            exit_hooks: std::sync::Mutex::new(Some(Vec::new())),
+        });
+
+        let exit = ExitGuard {
+            shared: shared.clone(),
+            _exit: exit,
+        };
+
+        (
+            Self {
+                send: Arc::new(send),
+                shared,
+            },
+            recv,
+            exit,
+        )
     }
 
     /// Get a type-erased BoxGhostActor version of this handle.
@@ -49,6 +756,14 @@ impl<T: 'static + Send> GhostActor<T> {
         BoxGhostActor(self.__box_clone())
     }
 
+    /// Get a weak handle to this actor, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakGhostActor<T> {
+        WeakGhostActor {
+            send: Arc::downgrade(&self.send),
+            shared: self.shared.clone(),
+        }
+    }
+
     /// Push state read/mutation logic onto actor queue for processing,
     /// uses `invoke()` internally - but expects a future to be returned
     /// which is `await`ed internally to be more ergonomic.
@@ -63,55 +778,1162 @@ impl<T: 'static + Send> GhostActor<T> {
     }
 
     /// Push state read/mutation logic onto actor queue for processing.
+    /// If the actor was configured with a default `invoke_timeout`,
+    /// it will be applied to this invocation.
     pub fn invoke<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
     where
         R: 'static + Send,
         E: 'static + From<GhostError> + Send,
         F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
     {
-        let mut sender = (*self.0).clone();
-        resp(
+        self.invoke_with(
+            self.shared.invoke_timeout,
+            self.shared.cancel_on_drop,
+            SendMode::Policy,
+            invoke,
+        )
+    }
+
+    /// Push state read/mutation logic onto actor queue for processing.
+    /// If the result is not available within `timeout`, resolves to
+    /// a `GhostErrorKind::Timeout` error. Overrides
+    /// `GhostConfig::invoke_timeout` for this invocation.
+    pub fn invoke_timeout<R, E, F>(
+        &self,
+        timeout: std::time::Duration,
+        invoke: F,
+    ) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        self.invoke_with(
+            Some(timeout),
+            self.shared.cancel_on_drop,
+            SendMode::Policy,
+            invoke,
+        )
+    }
+
+    /// Push state read/mutation logic onto actor queue for processing.
+    /// If the returned future is dropped before the actor reaches this
+    /// invocation, the logic is skipped entirely.
+    pub fn invoke_cancelable<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        self.invoke_with(
+            self.shared.invoke_timeout,
+            true,
+            SendMode::Policy,
+            invoke,
+        )
+    }
+
+    /// Push state read/mutation logic onto actor queue for processing,
+    /// as in `invoke()`. The logic is also handed a context, through which
+    /// it can emit events to subscribers.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::stream::StreamExt;
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct Incremented(u32);
+    ///
+    /// let (actor, driver) = GhostActor::new(0_u32);
+    /// tokio::task::spawn(driver);
+    ///
+    /// let mut events = actor.subscribe::<Incremented>();
+    /// actor.invoke_ctx(|i, ctx| {
+    ///     *i += 1;
+    ///     ctx.emit(Incremented(*i));
+    ///     <Result<(), GhostError>>::Ok(())
+    /// }).await.unwrap();
+    ///
+    /// assert_eq!(Some(Incremented(1)), events.next().await);
+    /// # }
+    /// ```
+    pub fn invoke_ctx<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T, &GhostContext<'_>) -> Result<R, E> + 'static + Send,
+    {
+        let shared = self.shared.clone();
+        self.invoke(move |t| {
+            invoke(
+                t,
+                &GhostContext {
+                    events: &shared.events,
+                },
+            )
+        })
+    }
+
+    /// Subscribe to events of type `Ev` emitted by invocations through
+    /// `GhostContext::emit()`, buffering up to 64 events, and dropping
+    /// the oldest if the subscriber falls behind.
+    pub fn subscribe<Ev: 'static + Clone + Send>(
+        &self,
+    ) -> GhostSubscription<Ev> {
+        self.subscribe_with(64, GhostLagPolicy::DropOldest)
+    }
+
+    /// Subscribe to events of type `Ev` emitted by invocations through
+    /// `GhostContext::emit()`, buffering up to `capacity` events, and
+    /// applying `policy` if the subscriber falls behind.
+    pub fn subscribe_with<Ev: 'static + Clone + Send>(
+        &self,
+        capacity: usize,
+        policy: GhostLagPolicy,
+    ) -> GhostSubscription<Ev> {
+        self.shared.events.subscribe(capacity, policy)
+    }
+
+    /// Push state read/mutation logic onto actor queue for processing,
+    /// without waiting for mailbox capacity. If the mailbox is full, the
+    /// returned future immediately resolves to a
+    /// `GhostErrorKind::MailboxFull` error (unless the configured
+    /// `GhostOverflowPolicy` drops invocations to make room instead).
+    pub fn try_invoke<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        self.invoke_with(
+            self.shared.invoke_timeout,
+            self.shared.cancel_on_drop,
+            SendMode::Try,
+            invoke,
+        )
+    }
+
+    /// Push async state read/mutation logic onto actor queue for processing.
+    /// Unlike `invoke_async()`, the returned future is driven by the actor
+    /// itself, retaining exclusive access to the state across `await` points.
+    /// No other invocations are processed until it completes.
+    pub fn invoke_exclusive<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: for<'a> FnOnce(
+                &'a mut T,
+            )
+                -> futures::future::BoxFuture<'a, Result<R, E>>
+            + 'static
+            + Send,
+    {
+        let cancel_on_drop = self.shared.cancel_on_drop;
+        let fut = self.invoke_raw(
+            SendMode::Policy,
+            move |responder: Responder<R, E>| {
+                InnerInvoke::exclusive(move |t| {
+                    Box::pin(responder.run_async(cancel_on_drop, async move {
+                        invoke(t).await
+                    }))
+                })
+            },
+        );
+        self.shared.with_timeout(self.shared.invoke_timeout, fut)
+    }
+
+    /// Push read-only state logic onto actor queue for processing.
+    /// Consecutive reads in the queue may be processed concurrently on
+    /// `GhostConfig::read_pool`, while writes (every other kind of
+    /// invocation) remain exclusive. By default reads run one at a time.
+    pub fn invoke_read<R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        T: Sync,
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&T) -> Result<R, E> + 'static + Send,
+    {
+        let cancel_on_drop = self.shared.cancel_on_drop;
+        let fut = self.invoke_raw(
+            SendMode::Policy,
+            move |responder: Responder<R, E>| {
+                InnerInvoke::Read(
+                    Box::new(move |t: &T| {
+                        responder.run(cancel_on_drop, || invoke(t))
+                    }),
+                    run_reads::<T>,
+                )
+            },
+        );
+        self.shared.with_timeout(self.shared.invoke_timeout, fut)
+    }
+
+    /// Push state mutation logic onto actor queue for processing,
+    /// without waiting for, or even being able to receive, a result.
+    /// Avoids the overhead of setting up a response channel and tracing
+    /// context. Resolves once the logic has been queued. Under
+    /// `GhostOverflowPolicy::DropOldest` / `DropNewest`, the notification
+    /// may later be discarded to make room, and nobody is told.
+    pub fn notify<F>(&self, notify: F) -> GhostFuture<(), GhostError>
+    where
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        self.notify_with(SendMode::Policy, notify)
+    }
+
+    fn notify_with<F>(
+        &self,
+        mode: SendMode,
+        notify: F,
+    ) -> GhostFuture<(), GhostError>
+    where
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        let sender = self.send.clone();
+        let shared = self.shared.clone();
+        resp(async move {
+            if shared.is_poisoned() {
+                return Err(GhostErrorKind::Poisoned.into());
+            }
+
+            let notify = InnerInvoke::Sync(Box::new(notify));
+            match mode {
+                SendMode::Wait => sender.send_wait(notify).await,
+                _ => sender.send(notify).await,
+            }
+            .map_err(|e| e.into_ghost_error(|e| shared.closed_error(e)))
+        })
+    }
+
+    /// Push state mutation logic onto actor queue for processing,
+    /// as in `notify()`, but without waiting.
+    /// Returns a `GhostErrorKind::MailboxFull` error if the mailbox is at
+    /// capacity under `GhostOverflowPolicy::Block` / `Reject`, or another
+    /// error if the actor is no longer accepting invocations. Under
+    /// `DropOldest` / `DropNewest`, this returns `Ok(())` even at capacity,
+    /// and a notification may be discarded without anybody being told.
+    pub fn try_notify<F>(&self, notify: F) -> Result<(), GhostError>
+    where
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        if self.shared.is_poisoned() {
+            return Err(GhostErrorKind::Poisoned.into());
+        }
+
+        self.send
+            .try_send(InnerInvoke::Sync(Box::new(notify)))
+            .map_err(|e| e.into_ghost_error(|e| self.shared.closed_error(e)))
+    }
+
+    /// Push state mutation logic onto actor queue for processing
+    /// once `delay` has elapsed, as measured by `GhostConfig::timer`.
+    /// If the mailbox is full by then, the logic waits for room,
+    /// whatever the `GhostConfig::overflow_policy`.
+    pub fn invoke_after<F>(
+        &self,
+        delay: std::time::Duration,
+        invoke: F,
+    ) -> GhostTimerHandle
+    where
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        let weak = self.downgrade();
+        let sleep = self.shared.timer.sleep(delay);
+        self.spawn_timer(async move {
+            sleep.await;
+            if let Some(actor) = weak.upgrade() {
+                let _ = actor.notify_with(SendMode::Wait, invoke).await;
+            }
+        })
+    }
+
+    /// Push state mutation logic onto actor queue for processing
+    /// every `interval`, as measured by `GhostConfig::timer`. Ticks are
+    /// scheduled relative to when the timer was created, so they do not
+    /// drift. If the actor falls behind, missed ticks are queued at once.
+    /// If the mailbox is full, ticks wait for room, whatever the
+    /// `GhostConfig::overflow_policy`. The timer stops if `invoke` panics.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let (actor, driver) = GhostActor::new(0_u32);
+    /// tokio::task::spawn(driver);
+    ///
+    /// let ticker = actor.invoke_every(
+    ///     std::time::Duration::from_millis(1),
+    ///     |ticks| *ticks += 1,
+    /// );
+    /// tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    /// ticker.cancel();
+    ///
+    /// let ticks = actor.invoke(|t| <Result<u32, GhostError>>::Ok(*t)).await.unwrap();
+    /// assert!(ticks > 0);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn invoke_every<F>(
+        &self,
+        interval: std::time::Duration,
+        invoke: F,
+    ) -> GhostTimerHandle
+    where
+        F: FnMut(&mut T) + 'static + Send,
+    {
+        assert!(
+            !interval.is_zero(),
+            "invoke_every interval must not be zero"
+        );
+        let weak = self.downgrade();
+        let timer = self.shared.timer.clone();
+        let invoke = Arc::new(std::sync::Mutex::new(invoke));
+        self.spawn_timer(async move {
+            let mut next = std::time::Instant::now();
+            loop {
+                next = match next.checked_add(interval) {
+                    Some(next) => next,
+                    // too far out to represent, it would never fire
+                    None => return,
+                };
+                timer
+                    .sleep(
+                        next.saturating_duration_since(
+                            std::time::Instant::now(),
+                        ),
+                    )
+                    .await;
+
+                // a tick panicked under GhostPanicPolicy::Continue,
+                // leaving the closure in an unknown state
+                if invoke.is_poisoned() {
+                    return;
+                }
+
+                let actor = match weak.upgrade() {
+                    Some(actor) => actor,
+                    None => return,
+                };
+                let invoke = invoke.clone();
+                let res = actor
+                    .notify_with(SendMode::Wait, move |t| {
+                        if let Ok(mut invoke) = invoke.lock() {
+                            invoke(t);
+                        }
+                    })
+                    .await;
+                // we wait for room, so this means the actor is gone
+                if res.is_err() {
+                    return;
+                }
+            }
+        })
+    }
+
+    /// Run a timer task on the driver, so we stay executor agnostic.
+    fn spawn_timer<Fut>(&self, task: Fut) -> GhostTimerHandle
+    where
+        Fut: std::future::Future<Output = ()> + 'static + Send,
+    {
+        GhostTimerHandle(self.spawn_abortable(task))
+    }
+
+    /// Have the driver poll `task` until it completes, is aborted,
+    /// or the driver stops.
+    fn spawn_abortable<Fut>(&self, task: Fut) -> futures::future::AbortHandle
+    where
+        Fut: std::future::Future<Output = ()> + 'static + Send,
+    {
+        let (task, handle) = futures::future::abortable(task);
+        self.shared.spawn(Box::pin(async move {
+            let _ = task.await;
+        }));
+        handle
+    }
+
+    /// Drive `stream` from the actor task, applying `handler` to the state
+    /// for each item. Items are handled one at a time, each taking its
+    /// turn in the mailbox like any other invocation, so a busy stream
+    /// cannot starve other callers. The next item is not pulled until the
+    /// previous one has been handled, so backpressure reaches the stream.
+    /// Items wait for room in a full mailbox, whatever the
+    /// `GhostConfig::overflow_policy`, though they may still be evicted
+    /// by other senders under `GhostOverflowPolicy::DropOldest`.
+    /// Stops when the stream ends, `handler` panics, or the actor shuts
+    /// down. If polling the stream panics, it is dropped and the panic is
+    /// handled according to `GhostConfig::panic_policy`.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let (actor, driver) = GhostActor::new(0_u32);
+    /// tokio::task::spawn(driver);
+    ///
+    /// let (mut send, recv) = futures::channel::mpsc::channel(8);
+    /// actor.attach_stream(recv, |sum, n: u32| *sum += n);
+    ///
+    /// use futures::sink::SinkExt;
+    /// send.send(1).await.unwrap();
+    /// send.send(2).await.unwrap();
+    /// # drop(send);
+    /// # tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    /// # assert_eq!(3, actor.invoke(|s| <Result<u32, GhostError>>::Ok(*s)).await.unwrap());
+    /// # }
+    /// ```
+    pub fn attach_stream<S, H>(
+        &self,
+        stream: S,
+        handler: H,
+    ) -> GhostStreamHandle
+    where
+        S: futures::stream::Stream + 'static + Send,
+        S::Item: 'static + Send,
+        H: FnMut(&mut T, S::Item) + 'static + Send,
+    {
+        let weak = self.downgrade();
+        // only ever locked by the actor, one item at a time
+        let handler = Arc::new(std::sync::Mutex::new(handler));
+        GhostStreamHandle(self.spawn_abortable(async move {
+            futures::pin_mut!(stream);
+            while let Some(item) =
+                futures::stream::StreamExt::next(&mut stream).await
+            {
+                let actor = match weak.upgrade() {
+                    Some(actor) => actor,
+                    None => return,
+                };
+                let handler = handler.clone();
+                let res = actor
+                    .invoke_with(None, false, SendMode::Wait, move |t| {
+                        if let Ok(mut handler) = handler.lock() {
+                            handler(t, item);
+                        }
+                        <Result<(), GhostError>>::Ok(())
+                    })
+                    .await;
+                match res.as_ref().map_err(GhostError::kind) {
+                    // evicted by another sender, carry on with the next item
+                    Ok(()) | Err(Some(GhostErrorKind::Dropped)) => (),
+                    // closed, poisoned, shut down, or the handler panicked
+                    Err(_) => return,
+                }
+            }
+        }))
+    }
+
+    /// Start building a batch of invocations that will be queued as a
+    /// single mailbox entry, so that no other invocation can interleave
+    /// between them.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let (actor, driver) = GhostActor::new(0_u32);
+    /// tokio::task::spawn(driver);
+    ///
+    /// let mut batch = actor.batch();
+    /// let a = batch.invoke(|i| {
+    ///     *i += 1;
+    ///     <Result<u32, GhostError>>::Ok(*i)
+    /// });
+    /// let b = batch.invoke(|i| <Result<String, GhostError>>::Ok(i.to_string()));
+    /// batch.send().await.unwrap();
+    ///
+    /// assert_eq!(1, a.await.unwrap());
+    /// assert_eq!("1", &b.await.unwrap());
+    /// # }
+    /// ```
+    pub fn batch(&self) -> GhostBatch<T> {
+        GhostBatch {
+            actor: self.clone(),
+            invokes: Vec::new(),
+        }
+    }
+
+    /// Stream results out of the actor state in chunks of up to `capacity`
+    /// items. `invoke` pushes items into the sink it is handed, and calls
+    /// `finish()` on the sink once there is nothing more to send. It is
+    /// invoked again for the next chunk only once the caller has consumed
+    /// the previous one, so other invocations can run in between and the
+    /// results are never buffered in full. If `invoke` returns an error,
+    /// it is yielded after any items already pushed, and the stream ends.
+    /// Each chunk must make progress: if `invoke` neither pushes an item
+    /// nor calls `finish()`, the stream ends with an error.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let (actor, driver) = GhostActor::new((0..100).collect::<Vec<u32>>());
+    /// tokio::task::spawn(driver);
+    ///
+    /// let mut offset = 0;
+    /// let stream = actor.invoke_stream(10, move |peers, sink| {
+    ///     for peer in &peers[offset..] {
+    ///         if sink.push(*peer).is_err() {
+    ///             break;
+    ///         }
+    ///         offset += 1;
+    ///     }
+    ///     if offset == peers.len() {
+    ///         sink.finish();
+    ///     }
+    ///     <Result<(), GhostError>>::Ok(())
+    /// });
+    ///
+    /// use futures::stream::TryStreamExt;
+    /// let all: Vec<u32> = stream.try_collect().await.unwrap();
+    /// assert_eq!(100, all.len());
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn invoke_stream<I, E, F>(
+        &self,
+        capacity: usize,
+        invoke: F,
+    ) -> GhostStream<I, E>
+    where
+        I: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnMut(&mut T, &mut GhostStreamSink<I>) -> Result<(), E>
+            + 'static
+            + Send,
+    {
+        assert!(capacity > 0, "invoke_stream capacity must not be zero");
+
+        struct State<T: 'static + Send, I, E, F> {
+            actor: GhostActor<T>,
+            buffer: VecDeque<I>,
+            // None once the logic has finished or failed
+            invoke: Option<F>,
+            error: Option<E>,
+        }
+
+        let state = State {
+            actor: self.clone(),
+            buffer: VecDeque::new(),
+            invoke: Some(invoke),
+            error: None,
+        };
+
+        GhostStream::new(futures::stream::unfold(state, move |mut state| {
             async move {
-                // capture tracing context
-                let strong = Arc::new(tracing::Span::current());
-                let weak = Arc::downgrade(&strong);
+                loop {
+                    if let Some(item) = state.buffer.pop_front() {
+                        return Some((Ok(item), state));
+                    }
+
+                    if let Some(e) = state.error.take() {
+                        return Some((Err(e), state));
+                    }
 
-                // set up oneshot result channel
-                let (o_send, o_recv) = futures::channel::oneshot::channel();
+                    let mut invoke = state.invoke.take()?;
 
-                // construct logic closure
-                let inner: InnerInvoke<T> = Box::new(move |t: &mut T| {
-                    let strong = weak.upgrade().unwrap_or_else(|| {
-                        tracing::warn!("TRACING: Parent context dropped");
-                        Arc::new(tracing::Span::current())
-                    });
-                    strong.in_scope(|| {
-                        let r = invoke(t);
-                        let _ = o_send.send(r);
-                    });
+                    // hand the logic to the actor and back, to avoid locking
+                    let res = state
+                        .actor
+                        .invoke(move |t| {
+                            let mut sink = GhostStreamSink::new(capacity);
+                            let res = invoke(t, &mut sink);
+                            <Result<_, E>>::Ok((invoke, sink, res))
+                        })
+                        .await;
+
+                    match res {
+                        Ok((invoke, sink, res)) => {
+                            let (items, finished) = sink.into_parts();
+                            state.buffer = items;
+                            match res {
+                                // don't spin on logic that will never finish
+                                Ok(())
+                                    if !finished && state.buffer.is_empty() =>
+                                {
+                                    let e = GhostError::from(
+                                        "invoke_stream logic made no progress",
+                                    );
+                                    state.error = Some(e.into());
+                                }
+                                Ok(()) if !finished => {
+                                    state.invoke = Some(invoke);
+                                }
+                                Ok(()) => (),
+                                // yield the items pushed before the error
+                                Err(e) => state.error = Some(e),
+                            }
+                        }
+                        Err(e) => state.error = Some(e),
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Watch a value selected from the actor state. The returned stream
+    /// yields the current value, then a new value each time it changes.
+    /// `selector` is evaluated by the driver after each batch of
+    /// invocations, so keep it cheap.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::stream::StreamExt;
+    ///
+    /// let (actor, driver) = GhostActor::new(0_u32);
+    /// tokio::task::spawn(driver);
+    ///
+    /// let mut is_even = actor.watch(|n| n % 2 == 0);
+    /// assert_eq!(Some(true), is_even.next().await);
+    ///
+    /// actor.invoke(|n| { *n += 1; <Result<(), GhostError>>::Ok(()) }).await.unwrap();
+    /// assert_eq!(Some(false), is_even.next().await);
+    /// # }
+    /// ```
+    pub fn watch<V, S>(&self, selector: S) -> GhostWatch<V>
+    where
+        V: 'static + Clone + PartialEq + Send,
+        S: Fn(&T) -> V + 'static + Send,
+    {
+        let (sender, watch) = watch::channel();
+        let mut last = None;
+        let watcher: Watcher<T> = Box::new(move |t: &T| {
+            let value = selector(t);
+            if last.as_ref() == Some(&value) {
+                return !sender.is_closed();
+            }
+            last = Some(value.clone());
+            sender.send(value)
+        });
+
+        // registered outside the mailbox, so it is never held up
+        // or dropped by the overflow policy
+        self.shared.watch(Box::new(watcher));
+
+        watch
+    }
+
+    /// Push a list of state read/mutation logic closures onto actor queue
+    /// as a single entry, so that no other invocation can interleave
+    /// between them. Resolves to the result of each closure, in order.
+    /// If a closure panics, its result is a `GhostErrorKind::Panicked`
+    /// error, and the closures after it are skipped.
+    /// See `batch()` for closures with differing types.
+    pub fn invoke_batch<R, E, F, I>(
+        &self,
+        invokes: I,
+    ) -> GhostFuture<Vec<Result<R, E>>, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+        I: IntoIterator<Item = F>,
+    {
+        let mut batch = self.batch();
+        let results = invokes
+            .into_iter()
+            .map(|invoke| batch.invoke(invoke))
+            .collect::<Vec<_>>();
+        let sent = batch.send();
+        resp(async move {
+            sent.await?;
+            Ok(futures::future::join_all(results).await)
+        })
+    }
+
+    fn invoke_with<R, E, F>(
+        &self,
+        timeout: Option<std::time::Duration>,
+        cancel_on_drop: bool,
+        mode: SendMode,
+        invoke: F,
+    ) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        let fut = self.invoke_raw(mode, move |responder: Responder<R, E>| {
+            InnerInvoke::Sync(sync_invoke(cancel_on_drop, responder, invoke))
+        });
+        self.shared.with_timeout(timeout, fut)
+    }
+
+    /// Forward logic built by `make` to the actor task driver,
+    /// and await the response, sending as `mode` dictates.
+    fn invoke_raw<R, E, M>(&self, mode: SendMode, make: M) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        M: FnOnce(Responder<R, E>) -> InnerInvoke<T> + 'static + Send,
+    {
+        let sender = self.send.clone();
+        let shared = self.shared.clone();
+
+        if let SendMode::Try = mode {
+            if shared.is_poisoned() {
+                return resp(async move {
+                    Err(GhostError::from(GhostErrorKind::Poisoned).into())
                 });
+            }
+
+            let (responder, response) = Responder::new(shared.clone());
+            if let Err(e) = sender.try_send(make(responder)) {
+                let err = e.into_ghost_error(|e| shared.closed_error(e));
+                return resp(async move { Err(err.into()) });
+            }
+
+            return resp(response.instrument(tracing::Span::current()));
+        }
+
+        resp(
+            async move {
+                if shared.is_poisoned() {
+                    return Err(
+                        GhostError::from(GhostErrorKind::Poisoned).into()
+                    );
+                }
+
+                // construct logic closure
+                let (responder, response) = Responder::new(shared.clone());
+                let inner = make(responder);
 
                 // forward logic closure to actor task driver
-                use futures::sink::SinkExt;
-                sender.send(inner).await.map_err(GhostError::other)?;
+                match mode {
+                    SendMode::Wait => sender.send_wait(inner).await,
+                    _ => sender.send(inner).await,
+                }
+                .map_err(|e| e.into_ghost_error(|e| shared.closed_error(e)))?;
 
                 // await response
-                o_recv.await.map_err(GhostError::other)?
+                response.await
             }
             .instrument(tracing::Span::current()),
         )
     }
 
     /// Returns `true` if the channel is still connected to the actor task.
+    /// A poisoned actor is never considered active.
     pub fn is_active(&self) -> bool {
-        !self.0.is_closed()
+        !self.send.is_closed() && !self.shared.is_poisoned()
+    }
+
+    /// Access the data shared by all handles to this actor.
+    pub(crate) fn shared(&self) -> &Arc<Shared> {
+        &self.shared
+    }
+
+    /// The name of this actor, see `GhostConfig::name`.
+    pub fn name(&self) -> Option<&str> {
+        self.shared.name.as_deref()
+    }
+
+    /// A snapshot of the actor metrics,
+    /// or `None` if `GhostConfig::metrics` was not enabled.
+    pub fn metrics(&self) -> Option<GhostMetrics> {
+        self.shared.metrics.as_ref().map(|metrics| {
+            metrics.snapshot(self.send.len(), Arc::strong_count(&self.send))
+        })
     }
 
     /// Close the channel to the actor task.
     /// This will result in the task being dropped once all pending invocations
     /// have been processed.
     pub fn shutdown(&self) {
-        (*self.0).clone().close_channel();
+        self.send.close();
+    }
+
+    /// Close the channel to the actor task.
+    /// The returned future resolves once all pending invocations have been
+    /// processed and the driver has completed. The shutdown is initiated
+    /// immediately, the future may be dropped if you don't need to wait.
+    pub fn shutdown_graceful(&self) -> GhostFuture<(), GhostError> {
+        self.shutdown();
+        let exited = self.shared.exited.clone();
+        resp(async move {
+            // the sender is only ever dropped, never sent on
+            let _ = exited.await;
+            Ok(())
+        })
+    }
+
+    /// Close the channel to the actor task, discarding any pending
+    /// invocations. Their callers receive a `GhostErrorKind::Shutdown` error.
+    /// An invocation already in progress is allowed to complete.
+    /// The returned future resolves once the driver has completed.
+    /// The shutdown is initiated immediately, the future may be dropped
+    /// if you don't need to wait.
+    pub fn shutdown_now(&self) -> GhostFuture<(), GhostError> {
+        self.shared.aborted.store(true, Ordering::SeqCst);
+        self.shutdown_graceful()
+    }
+
+    /// Gracefully shut down the actor task as in `shutdown_graceful()`,
+    /// but if pending invocations have not been processed within `timeout`,
+    /// fall back to `shutdown_now()`.
+    pub fn shutdown_graceful_timeout(
+        &self,
+        timeout: std::time::Duration,
+    ) -> GhostFuture<(), GhostError> {
+        let graceful = self.shutdown_graceful();
+        let this = self.clone();
+        resp(async move {
+            let timer = &*this.shared.timer;
+            if timer::timeout(timer, timeout, graceful).await.is_err() {
+                tracing::warn!(
+                    ?timeout,
+                    "GhostActor graceful shutdown timed out, aborting",
+                );
+                this.shutdown_now().await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Wrap invoke logic to report its result or panic through `responder`.
+fn sync_invoke<T, R, E, F>(
+    cancel_on_drop: bool,
+    responder: Responder<R, E>,
+    invoke: F,
+) -> SyncInvoke<T>
+where
+    T: 'static + Send,
+    R: 'static + Send,
+    E: 'static + From<GhostError> + Send,
+    F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+{
+    Box::new(move |t: &mut T| responder.run(cancel_on_drop, || invoke(t)))
+}
+
+/// Run a group of read-only invocations, dealing them out across up to
+/// `read_concurrency` jobs on the configured read pool, if any, and
+/// blocking until they are done. Returns the first panic encountered.
+fn run_reads<T: Sync>(
+    t: &T,
+    reads: Vec<ReadInvoke<T>>,
+    config: &DriveConfig,
+) -> Result<(), Box<dyn std::any::Any + Send>> {
+    fn run_all<T>(
+        t: &T,
+        reads: Vec<ReadInvoke<T>>,
+    ) -> Result<(), Box<dyn std::any::Any + Send>> {
+        let mut out = Ok(());
+        for read in reads {
+            let res =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    read(t)
+                }));
+            if let (Err(e), true) = (res, out.is_ok()) {
+                out = Err(e);
+            }
+        }
+        out
+    }
+
+    let jobs = config.read_concurrency.min(reads.len());
+    let pool = match &config.read_pool {
+        Some(pool) if jobs > 1 => pool,
+        _ => return run_all(t, reads),
+    };
+
+    let mut groups = (0..jobs).map(|_| Vec::new()).collect::<Vec<_>>();
+    for (i, read) in reads.into_iter().enumerate() {
+        groups[i % jobs].push(read);
+    }
+
+    let out = std::sync::Mutex::new(Ok(()));
+    let jobs = groups
+        .into_iter()
+        .map(|group| {
+            let out = &out;
+            Box::new(move || {
+                if let Err(e) = run_all(t, group) {
+                    let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+                    if out.is_ok() {
+                        *out = Err(e);
+                    }
+                }
+            }) as GhostReadJob<'_>
+        })
+        .collect();
+    pool.run(jobs);
+    out.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// How an invocation is handed to the mailbox.
+#[derive(Clone, Copy)]
+enum SendMode {
+    /// Apply the overflow policy, waiting for room under `Block`.
+    Policy,
+
+    /// Never wait, failing with `MailboxFull` under `Block`.
+    Try,
+
+    /// Wait for room whatever the overflow policy, for logic the crate
+    /// queues on the caller's behalf, which should not be lost.
+    Wait,
+}
+
+/// Why a driver stopped processing invocations.
+enum DriverExit {
+    /// All handles were dropped or the actor was shut down.
+    Closed,
+
+    /// An invocation panicked under `GhostPanicPolicy::Shutdown`.
+    Panicked,
+}
+
+/// The subset of GhostConfig the driver loop needs.
+#[derive(Clone)]
+struct DriveConfig {
+    panic_policy: GhostPanicPolicy,
+    max_batch_size: usize,
+    yield_after_invokes: Option<usize>,
+    yield_after_duration: Option<std::time::Duration>,
+    read_concurrency: usize,
+    read_pool: Option<Arc<dyn GhostReadPool>>,
+}
+
+impl DriveConfig {
+    fn new(config: &GhostConfig) -> Self {
+        Self {
+            panic_policy: config.panic_policy,
+            max_batch_size: config.max_batch_size.max(1),
+            yield_after_invokes: config.yield_after_invokes,
+            yield_after_duration: config.yield_after_duration,
+            read_concurrency: config.read_concurrency,
+            read_pool: config.read_pool.clone(),
+        }
+    }
+}
+
+/// Tracks how much work the driver has done since it last yielded,
+/// or went idle waiting on the mailbox.
+struct YieldBudget {
+    invokes: usize,
+    since: Option<std::time::Instant>,
+}
+
+impl YieldBudget {
+    fn new(config: &DriveConfig) -> Self {
+        Self {
+            invokes: 0,
+            // only pay for reading the clock if we have a time budget
+            since: config
+                .yield_after_duration
+                .map(|_| std::time::Instant::now()),
+        }
+    }
+
+    /// Record an invocation, returns `true` if we should yield.
+    fn spend(&mut self, config: &DriveConfig) -> bool {
+        self.invokes += 1;
+        if let Some(max) = config.yield_after_invokes {
+            if self.invokes >= max {
+                return true;
+            }
+        }
+        if let (Some(max), Some(since)) =
+            (config.yield_after_duration, self.since)
+        {
+            if since.elapsed() >= max {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Yield control back to the executor once, letting other tasks run.
+async fn yield_now() {
+    let mut yielded = false;
+    futures::future::poll_fn(move |cx| {
+        if yielded {
+            return std::task::Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        std::task::Poll::Pending
+    })
+    .await
+}
+
+/// Apply the panic policy to a panic caught on the driver,
+/// returns `true` if the driver should stop.
+fn on_panic(
+    e: Panic,
+    source: &str,
+    shared: &Shared,
+    config: &DriveConfig,
+) -> bool {
+    let msg = panic_message(&*e);
+    match config.panic_policy {
+        GhostPanicPolicy::Continue => {
+            tracing::error!(%msg, "GhostActor {} panicked", source);
+            false
+        }
+        GhostPanicPolicy::Poison => {
+            tracing::error!(
+                %msg,
+                "GhostActor {} panicked, poisoning actor",
+                source,
+            );
+            shared.poisoned.store(true, Ordering::SeqCst);
+            false
+        }
+        GhostPanicPolicy::Shutdown => {
+            tracing::error!(%msg, "GhostActor {} panicked, stopping", source);
+            true
+        }
+    }
+}
+
+/// Process invocations against the state until the channel closes
+/// or the panic policy stops us. Invocations received but not yet
+/// processed are left in `backlog`.
+async fn drive<T: 'static, S>(
+    recv: &mut S,
+    state: &mut DriveState<T>,
+    t: &mut T,
+    shared: &Shared,
+    config: &DriveConfig,
+) -> DriverExit
+where
+    S: futures::stream::Stream<Item = Vec<Queued<InnerInvoke<T>>>> + Unpin,
+{
+    let DriveState {
+        backlog,
+        tasks,
+        watchers,
+        dirty,
+        processed: processed_hook,
+    } = state;
+    let mut budget = YieldBudget::new(config);
+    loop {
+        if backlog.is_empty() {
+            if std::mem::take(dirty) {
+                // the batch is complete, let watchers see the new state
+                watchers.retain_mut(|watcher| eval_watcher(t, watcher));
+            }
+
+            let next = futures::future::poll_fn(|cx| {
+                if let Some(e) = shared.poll_tasks(tasks, cx) {
+                    return std::task::Poll::Ready(Err(e));
+                }
+                shared.take_watchers(t, watchers);
+                futures::stream::StreamExt::poll_next_unpin(recv, cx).map(Ok)
+            });
+            let next = next.await;
+            // we were (or could have been) idle, the work starts over
+            budget = YieldBudget::new(config);
+            match next {
+                Err(e) => {
+                    if on_panic(e, "background task", shared, config) {
+                        return DriverExit::Panicked;
+                    }
+                }
+                Ok(Some(invokes)) => {
+                    backlog.extend(invokes);
+                    if let Some(metrics) = &shared.metrics {
+                        metrics.set_backlog(backlog.len());
+                    }
+                }
+                Ok(None) => return DriverExit::Closed,
+            }
+        }
+
+        while let Some(Queued {
+            msg: invoke,
+            queued_at,
+        }) = backlog.pop_front()
+        {
+            if shared.is_poisoned() || shared.is_aborted() {
+                // dropping the invoke fails the caller as poisoned / shutdown
+                continue;
+            }
+
+            // only touch the clock if metrics are enabled
+            let metrics = shared.metrics.as_ref();
+            let started = metrics.map(|_| std::time::Instant::now());
+            let mut processed = 1;
+            let record_queued = |queued_at: Option<std::time::Instant>| {
+                if let (Some(metrics), Some(queued_at), Some(started)) =
+                    (metrics, queued_at, started)
+                {
+                    metrics.record_queued(started - queued_at);
+                }
+            };
+            record_queued(queued_at);
+
+            // give invokes sequential access to mutable state
+            let res = match invoke {
+                InnerInvoke::Sync(invoke) => std::panic::catch_unwind(
+                    std::panic::AssertUnwindSafe(|| invoke(t)),
+                ),
+                InnerInvoke::Exclusive(invoke) => {
+                    futures::future::FutureExt::catch_unwind(
+                        std::panic::AssertUnwindSafe(async { invoke(t).await }),
+                    )
+                    .await
+                }
+                InnerInvoke::Read(invoke, run) => {
+                    // gather up any adjacent reads to run together
+                    let mut reads = vec![invoke];
+                    while let Some(Queued {
+                        msg: InnerInvoke::Read(..),
+                        ..
+                    }) = backlog.front()
+                    {
+                        if let Some(Queued {
+                            msg: InnerInvoke::Read(read, _),
+                            queued_at,
+                        }) = backlog.pop_front()
+                        {
+                            reads.push(read);
+                            record_queued(queued_at);
+                            processed += 1;
+                        }
+                    }
+                    run(t, reads, config)
+                }
+            };
+            *dirty = true;
+
+            if let (Some(metrics), Some(started)) = (metrics, started) {
+                metrics.record_exec(processed, started.elapsed());
+                metrics.set_backlog(backlog.len());
+            }
+
+            let panicked = res.is_err();
+            if let Err(e) = res {
+                if on_panic(e, "invocation", shared, config) {
+                    return DriverExit::Panicked;
+                }
+            }
+
+            if let Some(hook) = processed_hook {
+                // never hand out state an invocation just panicked in
+                if !panicked && !shared.is_poisoned() {
+                    hook(t, processed);
+                }
+            }
+
+            if budget.spend(config) {
+                // be fair to other tasks sharing our executor
+                yield_now().await;
+                budget = YieldBudget::new(config);
+            }
+        }
     }
 }
 
@@ -124,14 +1946,59 @@ impl<T: 'static + Send> AsGhostActor for GhostActor<T> {
         resp(fut)
     }
 
+    fn __invoke_timeout(
+        &self,
+        timeout: std::time::Duration,
+        invoke: RawInvokeClosure,
+    ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError> {
+        let fut = self.invoke_timeout(timeout, |t| invoke(t));
+        resp(fut)
+    }
+
+    fn __try_invoke(
+        &self,
+        invoke: RawInvokeClosure,
+    ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError> {
+        let fut = self.try_invoke(|t| invoke(t));
+        resp(fut)
+    }
+
+    fn __invoke_batch(
+        &self,
+        invokes: Vec<RawInvokeClosure>,
+    ) -> GhostFuture<RawBatchResults, GhostError> {
+        self.invoke_batch(
+            invokes.into_iter().map(|invoke| move |t: &mut T| invoke(t)),
+        )
+    }
+
+    fn __notify(
+        &self,
+        notify: RawNotifyClosure,
+    ) -> GhostFuture<(), GhostError> {
+        self.notify(|t| notify(t))
+    }
+
+    fn __try_notify(&self, notify: RawNotifyClosure) -> Result<(), GhostError> {
+        self.try_notify(|t| notify(t))
+    }
+
     fn __is_active(&self) -> bool {
         GhostActor::is_active(self)
     }
 
+    fn __metrics(&self) -> Option<GhostMetrics> {
+        GhostActor::metrics(self)
+    }
+
     fn __shutdown(&self) {
         GhostActor::shutdown(self);
     }
 
+    fn __downgrade(&self) -> Box<dyn AsWeakGhostActor> {
+        Box::new(self.downgrade())
+    }
+
     fn __box_debug(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Debug::fmt(self, f)
     }
@@ -141,15 +2008,22 @@ impl<T: 'static + Send> AsGhostActor for GhostActor<T> {
     }
 
     fn __box_eq(&self, o: &dyn std::any::Any) -> bool {
+        // when comparing two BoxGhostActors, we are handed the other box,
+        // let its concrete type do the comparison
+        if let Some(o) =
+            <dyn std::any::Any>::downcast_ref::<Box<dyn AsGhostActor>>(o)
+        {
+            return o.__box_eq(self);
+        }
         let o: &GhostActor<T> = match <dyn std::any::Any>::downcast_ref(o) {
             None => return false,
             Some(o) => o,
         };
-        self.0.same_receiver(&o.0)
+        self.send.same_receiver(&o.send)
     }
 
     fn __box_hash(&self, hasher: &mut dyn std::hash::Hasher) {
-        self.0.hash_receiver(&mut Box::new(hasher));
+        self.send.hash_receiver(&mut Box::new(hasher));
     }
 }
 
@@ -157,8 +2031,11 @@ impl<T: 'static + Send> std::fmt::Debug for GhostActor<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut hasher = std::collections::hash_map::DefaultHasher::new();
         self.__box_hash(&mut hasher);
-        f.debug_struct("GhostActor")
-            .field("type", &std::any::type_name::<T>())
+        let mut s = f.debug_struct("GhostActor");
+        if let Some(name) = &self.shared.name {
+            s.field("name", name);
+        }
+        s.field("type", &std::any::type_name::<T>())
             .field("hash", &std::hash::Hasher::finish(&hasher))
             .finish()
     }
@@ -166,13 +2043,16 @@ impl<T: 'static + Send> std::fmt::Debug for GhostActor<T> {
 
 impl<T: 'static + Send> std::clone::Clone for GhostActor<T> {
     fn clone(&self) -> Self {
-        Self(self.0.clone())
+        Self {
+            send: self.send.clone(),
+            shared: self.shared.clone(),
+        }
     }
 }
 
 impl<T: 'static + Send> std::cmp::PartialEq for GhostActor<T> {
     fn eq(&self, o: &Self) -> bool {
-        self.0.same_receiver(&o.0)
+        self.send.same_receiver(&o.send)
     }
 }
 
@@ -180,6 +2060,141 @@ impl<T: 'static + Send> std::cmp::Eq for GhostActor<T> {}
 
 impl<T: 'static + Send> std::hash::Hash for GhostActor<T> {
     fn hash<Hasher: std::hash::Hasher>(&self, state: &mut Hasher) {
-        self.0.hash_receiver(state);
+        self.send.hash_receiver(state);
+    }
+}
+
+/// A weak handle to a GhostActor, which does not keep the actor alive.
+/// Once all strong `GhostActor` handles are dropped, the actor shuts down,
+/// and `upgrade()` will return `None`.
+pub struct WeakGhostActor<T: 'static + Send> {
+    send: std::sync::Weak<SendInvoke<T>>,
+    shared: Arc<Shared>,
+}
+
+impl<T: 'static + Send> WeakGhostActor<T> {
+    /// Attempt to get a strong handle to the actor.
+    /// Returns `None` if all strong handles have been dropped.
+    pub fn upgrade(&self) -> Option<GhostActor<T>> {
+        Some(GhostActor {
+            send: self.send.upgrade()?,
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// Get a type-erased BoxWeakGhostActor version of this handle.
+    pub fn to_boxed(&self) -> BoxWeakGhostActor {
+        BoxWeakGhostActor(Box::new(self.clone()))
+    }
+}
+
+impl<T: 'static + Send> AsWeakGhostActor for WeakGhostActor<T> {
+    fn __upgrade(&self) -> Option<Box<dyn AsGhostActor>> {
+        let strong: Box<dyn AsGhostActor> = Box::new(self.upgrade()?);
+        Some(strong)
+    }
+
+    ghost_box_trait_impl_fns!(AsWeakGhostActor);
+}
+
+impl<T: 'static + Send> std::fmt::Debug for WeakGhostActor<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        std::hash::Hash::hash(self, &mut hasher);
+        f.debug_struct("WeakGhostActor")
+            .field("type", &std::any::type_name::<T>())
+            .field("hash", &std::hash::Hasher::finish(&hasher))
+            .finish()
+    }
+}
+
+impl<T: 'static + Send> std::clone::Clone for WeakGhostActor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            send: self.send.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+// The strong handle compares / hashes by channel receiver. We cannot reach
+// the receiver through a dead weak handle, but there is exactly one Shared
+// per receiver, so comparing that gives the same identity semantics.
+
+impl<T: 'static + Send> std::cmp::PartialEq for WeakGhostActor<T> {
+    fn eq(&self, o: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &o.shared)
+    }
+}
+
+impl<T: 'static + Send> std::cmp::Eq for WeakGhostActor<T> {}
+
+impl<T: 'static + Send> std::hash::Hash for WeakGhostActor<T> {
+    fn hash<Hasher: std::hash::Hasher>(&self, state: &mut Hasher) {
+        Arc::as_ptr(&self.shared).hash(state);
+    }
+}
+
+/// A set of invocations to be queued as a single mailbox entry,
+/// see `GhostActor::batch()`. The invocations run back to back, in order.
+/// If one of them panics, the rest are skipped.
+#[must_use = "a batch does nothing unless you `send()` it"]
+pub struct GhostBatch<T: 'static + Send> {
+    actor: GhostActor<T>,
+    invokes: Vec<SyncInvoke<T>>,
+}
+
+impl<T: 'static + Send> GhostBatch<T> {
+    /// Add state read/mutation logic to the batch. The returned future
+    /// resolves once the batch has been sent and this logic has run.
+    /// If the batch is dropped without being sent, it resolves to a
+    /// `GhostErrorKind::Dropped` error.
+    pub fn invoke<R, E, F>(&mut self, invoke: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        let shared = &self.actor.shared;
+        let (responder, response) = Responder::new(shared.clone());
+        self.invokes.push(sync_invoke(
+            shared.cancel_on_drop,
+            responder,
+            invoke,
+        ));
+        shared.with_timeout(
+            shared.invoke_timeout,
+            resp(response.instrument(tracing::Span::current())),
+        )
+    }
+
+    /// The number of invocations in the batch.
+    pub fn len(&self) -> usize {
+        self.invokes.len()
+    }
+
+    /// Returns `true` if no invocations have been added to the batch.
+    pub fn is_empty(&self) -> bool {
+        self.invokes.is_empty()
+    }
+
+    /// Push the batch onto the actor queue as a single entry.
+    /// Resolves once the batch has been queued.
+    pub fn send(self) -> GhostFuture<(), GhostError> {
+        let invokes = self.invokes;
+        self.actor.notify(move |t| {
+            for invoke in invokes {
+                invoke(t);
+            }
+        })
+    }
+}
+
+impl<T: 'static + Send> std::fmt::Debug for GhostBatch<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostBatch")
+            .field("actor", &self.actor)
+            .field("len", &self.invokes.len())
+            .finish()
     }
 }
@@ -19,6 +19,14 @@ pub mod ghost_actor_trait {
             + Send,
     >;
 
+    /// Result definition for AsGhostActor::__invoke_batch
+    pub type RawBatchResults =
+        Vec<Result<Box<dyn std::any::Any + 'static + Send>, GhostError>>;
+
+    /// Closure definition for AsGhostActor::__notify
+    pub type RawNotifyClosure =
+        Box<dyn FnOnce(&mut dyn std::any::Any) + 'static + Send>;
+
     /// Generic GhostActor Trait. You shouldn't need to deal with this
     /// unless you are implementing an alternate ghost_actor backend.
     ///
@@ -35,17 +43,75 @@ pub mod ghost_actor_trait {
             invoke: RawInvokeClosure,
         ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError>;
 
+        /// Raw type-erased invoke_timeout function.
+        /// You probably want to use a higher-level function
+        /// with better type safety.
+        fn __invoke_timeout(
+            &self,
+            timeout: std::time::Duration,
+            invoke: RawInvokeClosure,
+        ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError>;
+
+        /// Raw type-erased try_invoke function.
+        /// You probably want to use a higher-level function
+        /// with better type safety.
+        fn __try_invoke(
+            &self,
+            invoke: RawInvokeClosure,
+        ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError>;
+
+        /// Raw type-erased invoke_batch function.
+        /// You probably want to use a higher-level function
+        /// with better type safety.
+        fn __invoke_batch(
+            &self,
+            invokes: Vec<RawInvokeClosure>,
+        ) -> GhostFuture<RawBatchResults, GhostError>;
+
+        /// Raw type-erased notify function.
+        /// You probably want to use a higher-level function
+        /// with better type safety.
+        fn __notify(
+            &self,
+            notify: RawNotifyClosure,
+        ) -> GhostFuture<(), GhostError>;
+
+        /// Raw type-erased try_notify function.
+        /// You probably want to use a higher-level function
+        /// with better type safety.
+        fn __try_notify(
+            &self,
+            notify: RawNotifyClosure,
+        ) -> Result<(), GhostError>;
+
         /// Returns `true` if the channel is still connected to the actor task.
         fn __is_active(&self) -> bool;
 
+        /// A snapshot of the actor metrics, if enabled.
+        fn __metrics(&self) -> Option<GhostMetrics>;
+
         /// Close the channel to the actor task.
         /// This will result in the task being dropped once all pending invocations
         /// have been processed.
         fn __shutdown(&self);
 
+        /// Get a weak handle to the actor, which does not keep it alive.
+        fn __downgrade(&self) -> Box<dyn AsWeakGhostActor>;
+
         ghost_box_trait_fns!(AsGhostActor);
     }
     ghost_box_trait!(AsGhostActor);
+
+    /// Generic weak GhostActor Trait. You shouldn't need to deal with this
+    /// unless you are implementing an alternate ghost_actor backend.
+    pub trait AsWeakGhostActor: 'static + Send + Sync {
+        /// Attempt to get a strong handle to the actor.
+        /// Returns `None` if all strong handles have been dropped.
+        fn __upgrade(&self) -> Option<Box<dyn AsGhostActor>>;
+
+        ghost_box_trait_fns!(AsWeakGhostActor);
+    }
+    ghost_box_trait!(AsWeakGhostActor);
 }
 
 /// Newtype wrapping boxed type-erased trait-object version of GhostActor.
@@ -71,6 +137,24 @@ impl BoxGhostActor {
         resp(async move { fut.await??.await })
     }
 
+    /// Push state read/mutation logic onto actor queue for processing.
+    /// If the result is not available within `timeout`, resolves to
+    /// a `GhostErrorKind::Timeout` error. Overrides
+    /// `GhostConfig::invoke_timeout` for this invocation.
+    pub fn invoke_timeout<T, R, E, F>(
+        &self,
+        timeout: std::time::Duration,
+        invoke: F,
+    ) -> GhostFuture<R, E>
+    where
+        T: 'static + Send,
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        raw_response(self.__invoke_timeout(timeout, raw_invoke(invoke)))
+    }
+
     /// Push state read/mutation logic onto actor queue for processing.
     pub fn invoke<T, R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
     where
@@ -82,33 +166,69 @@ impl BoxGhostActor {
         // NOTE - we don't have to do any tracing trickery here
         //        it can all be handled by the concrete implementation
         //        of __invoke
+        raw_response(self.__invoke(raw_invoke(invoke)))
+    }
+
+    /// Push state read/mutation logic onto actor queue for processing,
+    /// without waiting for mailbox capacity. If the mailbox is full, the
+    /// returned future immediately resolves to a
+    /// `GhostErrorKind::MailboxFull` error.
+    pub fn try_invoke<T, R, E, F>(&self, invoke: F) -> GhostFuture<R, E>
+    where
+        T: 'static + Send,
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+    {
+        raw_response(self.__try_invoke(raw_invoke(invoke)))
+    }
+
+    /// Push a list of state read/mutation logic closures onto actor queue
+    /// as a single entry, so that no other invocation can interleave
+    /// between them. Resolves to the result of each closure, in order,
+    /// see `GhostActor::invoke_batch()`.
+    pub fn invoke_batch<T, R, E, F, I>(
+        &self,
+        invokes: I,
+    ) -> GhostFuture<Vec<Result<R, E>>, E>
+    where
+        T: 'static + Send,
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+        I: IntoIterator<Item = F>,
+    {
+        let invokes = invokes.into_iter().map(raw_invoke).collect();
+        let fut = self.__invoke_batch(invokes);
+        resp(
+            async move { Ok(fut.await?.into_iter().map(raw_result).collect()) },
+        )
+    }
 
-        let inner = Box::new(move |a: &mut dyn std::any::Any| {
-            let t: &mut T = match a.downcast_mut() {
-                None => {
-                    return Err(GhostError::from("invalid concrete type T"));
-                }
-                Some(t) => t,
-            };
-            let r: Box<dyn std::any::Any + 'static + Send> =
-                Box::new(invoke(t));
-            Ok(r)
-        });
-
-        let fut = self.__invoke(inner);
-
-        resp(async move {
-            let a: Box<dyn std::any::Any> = fut.await?;
-            let r: Result<R, E> = match a.downcast() {
-                Err(_) => {
-                    return Err(
-                        GhostError::from("invalid concrete type R").into()
-                    )
-                }
-                Ok(r) => *r,
-            };
-            r
-        })
+    /// Push state mutation logic onto actor queue for processing,
+    /// without waiting for, or even being able to receive, a result.
+    /// Resolves once the logic has been queued, the mailbox overflow
+    /// policy may still discard it, see `GhostActor::notify()`.
+    /// If `T` does not match the concrete actor type, the logic is
+    /// skipped and an error is traced.
+    pub fn notify<T, F>(&self, notify: F) -> GhostFuture<(), GhostError>
+    where
+        T: 'static + Send,
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        self.__notify(raw_notify(notify))
+    }
+
+    /// Push state mutation logic onto actor queue for processing,
+    /// as in `notify()`, but without waiting.
+    /// Returns an error if the actor is no longer accepting invocations,
+    /// or its mailbox is full, see `GhostActor::try_notify()`.
+    pub fn try_notify<T, F>(&self, notify: F) -> Result<(), GhostError>
+    where
+        T: 'static + Send,
+        F: FnOnce(&mut T) + 'static + Send,
+    {
+        self.__try_notify(raw_notify(notify))
     }
 
     /// Returns `true` if the channel is still connected to the actor task.
@@ -116,12 +236,83 @@ impl BoxGhostActor {
         self.__is_active()
     }
 
+    /// A snapshot of the actor metrics,
+    /// or `None` if `GhostConfig::metrics` was not enabled.
+    pub fn metrics(&self) -> Option<GhostMetrics> {
+        self.__metrics()
+    }
+
     /// Close the channel to the actor task.
     /// This will result in the task being dropped once all pending invocations
     /// have been processed.
     pub fn shutdown(&self) {
         self.__shutdown();
     }
+
+    /// Get a weak handle to this actor, which does not keep it alive.
+    pub fn downgrade(&self) -> BoxWeakGhostActor {
+        BoxWeakGhostActor(self.__downgrade())
+    }
+}
+
+/// Type-erase invoke logic, reporting a type mismatch to the caller.
+fn raw_invoke<T, R, E, F>(invoke: F) -> RawInvokeClosure
+where
+    T: 'static + Send,
+    R: 'static + Send,
+    E: 'static + From<GhostError> + Send,
+    F: FnOnce(&mut T) -> Result<R, E> + 'static + Send,
+{
+    Box::new(move |a: &mut dyn std::any::Any| {
+        let t: &mut T = match a.downcast_mut() {
+            None => {
+                return Err(GhostError::from("invalid concrete type T"));
+            }
+            Some(t) => t,
+        };
+        let r: Box<dyn std::any::Any + 'static + Send> = Box::new(invoke(t));
+        Ok(r)
+    })
+}
+
+/// Recover the concrete result of type-erased invoke logic.
+fn raw_response<R, E>(
+    fut: GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError>,
+) -> GhostFuture<R, E>
+where
+    R: 'static + Send,
+    E: 'static + From<GhostError> + Send,
+{
+    resp(async move { raw_result(fut.await) })
+}
+
+/// Recover the concrete result of type-erased invoke logic,
+/// once it has run.
+fn raw_result<R, E>(
+    res: Result<Box<dyn std::any::Any + 'static + Send>, GhostError>,
+) -> Result<R, E>
+where
+    R: 'static + Send,
+    E: 'static + From<GhostError> + Send,
+{
+    let a: Box<dyn std::any::Any> = res?;
+    match a.downcast() {
+        Err(_) => Err(GhostError::from("invalid concrete type R").into()),
+        Ok(r) => *r,
+    }
+}
+
+/// Type-erase notify logic. There is no caller to report a type mismatch to,
+/// so we can only trace it.
+fn raw_notify<T, F>(notify: F) -> RawNotifyClosure
+where
+    T: 'static + Send,
+    F: FnOnce(&mut T) + 'static + Send,
+{
+    Box::new(move |a: &mut dyn std::any::Any| match a.downcast_mut() {
+        None => tracing::error!("notify: invalid concrete type T"),
+        Some(t) => notify(t),
+    })
 }
 
 impl AsGhostActor for BoxGhostActor {
@@ -132,13 +323,68 @@ impl AsGhostActor for BoxGhostActor {
         self.0.__invoke(invoke)
     }
 
+    fn __invoke_timeout(
+        &self,
+        timeout: std::time::Duration,
+        invoke: RawInvokeClosure,
+    ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError> {
+        self.0.__invoke_timeout(timeout, invoke)
+    }
+
+    fn __try_invoke(
+        &self,
+        invoke: RawInvokeClosure,
+    ) -> GhostFuture<Box<dyn std::any::Any + 'static + Send>, GhostError> {
+        self.0.__try_invoke(invoke)
+    }
+
+    fn __invoke_batch(
+        &self,
+        invokes: Vec<RawInvokeClosure>,
+    ) -> GhostFuture<RawBatchResults, GhostError> {
+        self.0.__invoke_batch(invokes)
+    }
+
+    fn __notify(
+        &self,
+        notify: RawNotifyClosure,
+    ) -> GhostFuture<(), GhostError> {
+        self.0.__notify(notify)
+    }
+
+    fn __try_notify(&self, notify: RawNotifyClosure) -> Result<(), GhostError> {
+        self.0.__try_notify(notify)
+    }
+
     fn __is_active(&self) -> bool {
         self.0.__is_active()
     }
 
+    fn __metrics(&self) -> Option<GhostMetrics> {
+        self.0.__metrics()
+    }
+
     fn __shutdown(&self) {
         self.0.__shutdown();
     }
 
+    fn __downgrade(&self) -> Box<dyn AsWeakGhostActor> {
+        self.0.__downgrade()
+    }
+
     ghost_box_trait_impl_fns!(AsGhostActor);
 }
+
+/// Newtype wrapping boxed type-erased trait-object version of
+/// WeakGhostActor. Equality and hashing match the actor identity
+/// of `BoxGhostActor`.
+pub struct BoxWeakGhostActor(pub Box<dyn AsWeakGhostActor>);
+ghost_box_new_type!(BoxWeakGhostActor);
+
+impl BoxWeakGhostActor {
+    /// Attempt to get a strong handle to the actor.
+    /// Returns `None` if all strong handles have been dropped.
+    pub fn upgrade(&self) -> Option<BoxGhostActor> {
+        self.0.__upgrade().map(BoxGhostActor)
+    }
+}
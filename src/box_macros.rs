@@ -145,6 +145,14 @@ macro_rules! ghost_box_trait_impl_fns {
 
         #[inline]
         fn __box_eq(&self, o: &dyn ::std::any::Any) -> bool {
+            // when comparing two Box<dyn> instances, we are handed the
+            // other box, let its concrete type do the comparison
+            if let Some(o) = <dyn ::std::any::Any>::downcast_ref::<
+                ::std::boxed::Box<dyn $trait>,
+            >(o)
+            {
+                return o.__box_eq(self);
+            }
             let c: &Self = match <dyn ::std::any::Any>::downcast_ref(o) {
                 None => return false,
                 Some(c) => c,
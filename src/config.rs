@@ -1,13 +1,186 @@
+use crate::*;
+
 /// Configuration tuning parameters for GhostActors
 #[non_exhaustive]
 pub struct GhostConfig {
+    /// A name identifying this actor in `Debug` output
+    /// and in tracing spans.
+    /// Default: None.
+    pub name: Option<String>,
+
+    /// Static labels recorded on the driver and invocation tracing spans,
+    /// e.g. `vec![("shard", "3")]`.
+    /// Default: empty.
+    pub labels: Vec<(&'static str, &'static str)>,
+
     /// Channel bound for communicating with actor.
+    /// Ignored if `unbounded` is set.
     /// Default: 32.
     pub channel_bound: usize,
+
+    /// If `true`, the mailbox grows without limit, and callers never wait
+    /// for capacity. Pair with `high_water_mark` to notice runaway growth.
+    /// Default: false.
+    pub unbounded: bool,
+
+    /// Emit a `tracing` warning when the mailbox holds this many
+    /// invocations. This is only a signal, invocations are still accepted.
+    /// The warning is re-armed once the mailbox drains to half this size.
+    /// Default: None (no warning).
+    pub high_water_mark: Option<usize>,
+
+    /// What happens to new invocations while the mailbox
+    /// already holds `channel_bound` invocations.
+    /// Default: GhostOverflowPolicy::Block.
+    pub overflow_policy: GhostOverflowPolicy,
+
+    /// Deadline applied to every `invoke()` that does not specify its own
+    /// through `invoke_timeout()`. Expired invocations resolve to a
+    /// `GhostErrorKind::Timeout` error.
+    /// Default: None (wait forever).
+    pub invoke_timeout: Option<std::time::Duration>,
+
+    /// If `true`, every `invoke()` behaves like `invoke_cancelable()`:
+    /// logic whose caller has already dropped the result future is skipped
+    /// rather than run. Leave `false` if you rely on the side effects of
+    /// invocations whose results you discard.
+    /// Default: false.
+    pub cancel_on_drop: bool,
+
+    /// What the actor should do after an invocation panics.
+    /// The panicking caller always receives a `GhostErrorKind::Panicked`.
+    /// Default: GhostPanicPolicy::Shutdown.
+    pub panic_policy: GhostPanicPolicy,
+
+    /// The maximum number of invocations the driver pulls off the channel
+    /// at once. Larger batches mitigate task thrashing.
+    /// Values less than 1 are treated as 1.
+    /// Default: 1024.
+    pub max_batch_size: usize,
+
+    /// Yield to the executor after processing this many invocations
+    /// back to back, so a busy actor cannot starve other tasks.
+    /// Default: None (never yield while invocations are ready).
+    pub yield_after_invokes: Option<usize>,
+
+    /// Yield to the executor after processing invocations back to back
+    /// for this long, so a busy actor cannot starve other tasks.
+    /// Default: None (never yield while invocations are ready).
+    pub yield_after_duration: Option<std::time::Duration>,
+
+    /// The maximum number of groups consecutive `invoke_read()`
+    /// invocations are dealt into, to run concurrently on `read_pool`.
+    /// Default: 1 (reads are processed sequentially on the driver task,
+    /// and are never concurrent).
+    pub read_concurrency: usize,
+
+    /// Runs groups of consecutive `invoke_read()` invocations concurrently,
+    /// see `read_concurrency`. Reads borrow the state, so the driver blocks
+    /// the executor thread it runs on until each group has finished.
+    /// Default: None (reads are processed sequentially on the driver task,
+    /// whatever the `read_concurrency`).
+    pub read_pool: Option<std::sync::Arc<dyn GhostReadPool>>,
+
+    /// If `true`, the actor tracks queue depth, latency and throughput,
+    /// readable through `GhostActor::metrics()`. When `false`, nothing is
+    /// recorded and `metrics()` returns `None`.
+    /// Default: false.
+    pub metrics: bool,
+
+    /// The timer used for invocation timeouts, restart backoff, and
+    /// `invoke_after()` / `invoke_every()`.
+    /// Default: GhostThreadTimer.
+    pub timer: std::sync::Arc<dyn GhostTimer>,
 }
 
 impl Default for GhostConfig {
     fn default() -> Self {
-        Self { channel_bound: 32 }
+        Self {
+            name: None,
+            labels: Vec::new(),
+            channel_bound: 32,
+            unbounded: false,
+            high_water_mark: None,
+            overflow_policy: GhostOverflowPolicy::Block,
+            invoke_timeout: None,
+            cancel_on_drop: false,
+            panic_policy: GhostPanicPolicy::Shutdown,
+            max_batch_size: 1024,
+            yield_after_invokes: None,
+            yield_after_duration: None,
+            read_concurrency: 1,
+            read_pool: None,
+            metrics: false,
+            timer: std::sync::Arc::new(GhostThreadTimer),
+        }
+    }
+}
+
+/// How a GhostActor reacts to a panic inside an invocation, a
+/// `GhostLifecycle` hook, or a background task its driver polls,
+/// such as an attached stream. A panicking background task is always
+/// dropped. If `stopping()` panics, the driver resolves to
+/// `GhostErrorKind::Poisoned` unless the policy is `Continue`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GhostPanicPolicy {
+    /// Log the panic and keep processing invocations.
+    /// Note, the state may have been left partially mutated.
+    Continue,
+
+    /// Keep the actor task running, but fail all pending and future
+    /// invocations with `GhostErrorKind::Poisoned`.
+    Poison,
+
+    /// Stop the actor task. Pending and future invocations fail
+    /// with `GhostErrorKind::Poisoned`.
+    Shutdown,
+}
+
+/// How a GhostActor handles invocations arriving at a full mailbox.
+/// `try_invoke()` and `try_notify()` never wait, with `Block` they fail
+/// with `GhostErrorKind::MailboxFull` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GhostOverflowPolicy {
+    /// Wait until the actor makes room in the mailbox.
+    Block,
+
+    /// Fail the new invocation with `GhostErrorKind::MailboxFull`.
+    Reject,
+
+    /// Evict the oldest queued invocation to make room. The evicted
+    /// caller receives a `GhostErrorKind::Dropped` error.
+    DropOldest,
+
+    /// Discard the new invocation. Its caller receives a
+    /// `GhostErrorKind::Dropped` error.
+    DropNewest,
+}
+
+/// Restart policy for actors created with `GhostActor::new_supervised()`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy)]
+pub struct GhostRestartPolicy {
+    /// The maximum number of restarts allowed within `window`.
+    /// One more panic than this poisons the actor permanently.
+    /// Default: 3.
+    pub max_restarts: usize,
+
+    /// The sliding window over which restarts are counted.
+    /// Default: 60 seconds.
+    pub window: std::time::Duration,
+
+    /// How long to wait before rebuilding the state after a panic.
+    /// Invocations queue up in the meantime.
+    /// Default: zero.
+    pub backoff: std::time::Duration,
+}
+
+impl Default for GhostRestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            window: std::time::Duration::from_secs(60),
+            backoff: std::time::Duration::ZERO,
+        }
     }
 }
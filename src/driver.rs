@@ -1,3 +1,5 @@
+use crate::*;
+
 /// Driver future representing an actor task.
 /// Please spawn this into whatever executor framework you are using.
 #[must_use = "futures do nothing unless you `.await` or poll them"]
@@ -14,3 +16,35 @@ impl std::future::Future for GhostDriver {
         std::future::Future::poll(self.0.as_mut(), cx)
     }
 }
+
+/// Driver future representing an actor task, which resolves to the final
+/// state of the actor once all handles are dropped or the actor is shut
+/// down, and all pending invocations have been processed.
+/// Resolves to a `GhostErrorKind::Poisoned` error instead if the
+/// actor stopped because an invocation panicked.
+/// Please spawn this into whatever executor framework you are using.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GhostStateDriver<T: 'static + Send>(
+    pub(crate) futures::future::BoxFuture<'static, Result<T, GhostError>>,
+);
+
+impl<T: 'static + Send> std::future::Future for GhostStateDriver<T> {
+    type Output = Result<T, GhostError>;
+
+    #[inline]
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+    ) -> std::task::Poll<Self::Output> {
+        std::future::Future::poll(self.0.as_mut(), cx)
+    }
+}
+
+impl<T: 'static + Send> From<GhostStateDriver<T>> for GhostDriver {
+    fn from(d: GhostStateDriver<T>) -> Self {
+        GhostDriver(futures::future::FutureExt::boxed(async move {
+            // the final state is simply dropped
+            let _ = d.await;
+        }))
+    }
+}
@@ -17,6 +17,12 @@ impl GhostError {
     pub fn other<E: 'static + std::error::Error + Send + Sync>(e: E) -> Self {
         Self(Arc::new(e))
     }
+
+    /// If this error was raised by ghost_actor itself,
+    /// get the specific kind of failure.
+    pub fn kind(&self) -> Option<&GhostErrorKind> {
+        self.0.downcast_ref()
+    }
 }
 
 impl From<String> for GhostError {
@@ -43,3 +49,65 @@ impl From<&str> for GhostError {
 impl From<GhostError> for () {
     fn from(_: GhostError) -> Self {}
 }
+
+/// Specific failure conditions raised by ghost_actor itself.
+/// Use `GhostError::kind()` to distinguish these from other errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GhostErrorKind {
+    /// The invocation did not complete before its deadline.
+    Timeout,
+
+    /// The invocation panicked. Contains the panic message.
+    Panicked(String),
+
+    /// A previous invocation panicked, and the actor's `GhostPanicPolicy`
+    /// stopped it from processing any further invocations.
+    Poisoned,
+
+    /// The actor was shut down with `shutdown_now()`
+    /// before the invocation could be processed.
+    Shutdown,
+
+    /// The actor mailbox was at capacity.
+    MailboxFull,
+
+    /// The invocation was dropped before it could be processed. Either
+    /// the mailbox overflow policy discarded it, the driver future was
+    /// dropped, or it was part of a `GhostBatch` that was never sent.
+    Dropped,
+}
+
+impl std::fmt::Display for GhostErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "invocation timed out"),
+            Self::Panicked(msg) => write!(f, "invocation panicked: {}", msg),
+            Self::Poisoned => write!(f, "actor poisoned by a previous panic"),
+            Self::Shutdown => write!(f, "actor was shut down"),
+            Self::MailboxFull => write!(f, "actor mailbox is full"),
+            Self::Dropped => {
+                write!(f, "invocation was dropped before it could be processed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GhostErrorKind {}
+
+impl From<GhostErrorKind> for GhostError {
+    fn from(k: GhostErrorKind) -> Self {
+        GhostError::other(k)
+    }
+}
+
+/// Extract a human readable message from a caught panic payload.
+pub(crate) fn panic_message(e: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = e.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = e.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
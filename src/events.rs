@@ -0,0 +1,216 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// What to do when an event is emitted to a subscriber whose buffer is
+/// full. The actor never waits on a slow subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GhostLagPolicy {
+    /// Discard the oldest buffered event to make room.
+    DropOldest,
+
+    /// Discard the new event.
+    DropNewest,
+
+    /// Unsubscribe. The subscription ends after the buffered events.
+    Unsubscribe,
+}
+
+struct Subscriber<Ev> {
+    buffer: VecDeque<Ev>,
+    capacity: usize,
+    policy: GhostLagPolicy,
+    missed: u64,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<Ev> Subscriber<Ev> {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Deliver an event, returns `false` if the subscription has ended.
+    fn deliver(&mut self, event: Ev) -> bool {
+        if self.closed {
+            return false;
+        }
+        if self.buffer.len() >= self.capacity {
+            self.missed += 1;
+            match self.policy {
+                GhostLagPolicy::DropOldest => {
+                    self.buffer.pop_front();
+                }
+                GhostLagPolicy::DropNewest => return true,
+                GhostLagPolicy::Unsubscribe => {
+                    self.closed = true;
+                    self.wake();
+                    return false;
+                }
+            }
+        }
+        self.buffer.push_back(event);
+        self.wake();
+        true
+    }
+}
+
+type SubscriberSlot<Ev> = Arc<Mutex<Subscriber<Ev>>>;
+
+/// The subscribers to one event type, type-erased so
+/// topics for all event types can share a map.
+trait Topic: 'static + Send {
+    fn as_any(&mut self) -> &mut dyn Any;
+    fn close(&mut self);
+}
+
+impl<Ev: 'static + Send> Topic for Vec<SubscriberSlot<Ev>> {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn close(&mut self) {
+        for sub in self.drain(..) {
+            let mut sub = sub.lock().unwrap();
+            sub.closed = true;
+            sub.wake();
+        }
+    }
+}
+
+/// Fans events emitted by an actor out to its subscribers.
+pub(crate) struct EventBus {
+    /// None once the actor has stopped
+    topics: Mutex<Option<HashMap<TypeId, Box<dyn Topic>>>>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        Self {
+            topics: Mutex::new(Some(HashMap::new())),
+        }
+    }
+
+    pub(crate) fn subscribe<Ev: 'static + Send>(
+        &self,
+        capacity: usize,
+        policy: GhostLagPolicy,
+    ) -> GhostSubscription<Ev> {
+        let sub = Arc::new(Mutex::new(Subscriber {
+            buffer: VecDeque::new(),
+            capacity: capacity.max(1),
+            policy,
+            missed: 0,
+            closed: false,
+            waker: None,
+        }));
+
+        match self.topics.lock().unwrap().as_mut() {
+            Some(topics) => topics
+                .entry(TypeId::of::<Ev>())
+                .or_insert_with(|| Box::new(Vec::<SubscriberSlot<Ev>>::new()))
+                .as_any()
+                .downcast_mut::<Vec<SubscriberSlot<Ev>>>()
+                .expect("topic stored under the wrong TypeId")
+                .push(sub.clone()),
+            // the actor has stopped, there will be no events
+            None => sub.lock().unwrap().closed = true,
+        }
+
+        GhostSubscription(sub)
+    }
+
+    pub(crate) fn emit<Ev: 'static + Clone + Send>(&self, event: Ev) {
+        let mut topics = self.topics.lock().unwrap();
+        let subs = match topics
+            .as_mut()
+            .and_then(|topics| topics.get_mut(&TypeId::of::<Ev>()))
+            .and_then(|topic| {
+                topic.as_any().downcast_mut::<Vec<SubscriberSlot<Ev>>>()
+            }) {
+            Some(subs) => subs,
+            None => return,
+        };
+        subs.retain(|sub| sub.lock().unwrap().deliver(event.clone()));
+    }
+
+    /// End all subscriptions, called when the actor stops.
+    pub(crate) fn close(&self) {
+        let topics = self.topics.lock().unwrap().take();
+        for (_, mut topic) in topics.into_iter().flatten() {
+            topic.close();
+        }
+    }
+}
+
+/// Handed to `GhostActor::invoke_ctx()` logic,
+/// for interacting with the actor beyond its state.
+pub struct GhostContext<'a> {
+    pub(crate) events: &'a EventBus,
+}
+
+impl GhostContext<'_> {
+    /// Publish an event to all current subscribers of type `Ev`,
+    /// see `GhostActor::subscribe()`. Never waits on subscribers.
+    pub fn emit<Ev: 'static + Clone + Send>(&self, event: Ev) {
+        self.events.emit(event);
+    }
+}
+
+impl std::fmt::Debug for GhostContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostContext").finish()
+    }
+}
+
+/// Stream of events of type `Ev` emitted by an actor,
+/// see `GhostActor::subscribe()`. Ends when the actor shuts down.
+#[must_use = "streams do nothing unless polled"]
+pub struct GhostSubscription<Ev>(SubscriberSlot<Ev>);
+
+impl<Ev> GhostSubscription<Ev> {
+    /// The number of events this subscriber has missed
+    /// because its buffer was full.
+    pub fn missed(&self) -> u64 {
+        self.0.lock().unwrap().missed
+    }
+}
+
+impl<Ev> futures::stream::Stream for GhostSubscription<Ev> {
+    type Item = Ev;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut sub = self.0.lock().unwrap();
+        if let Some(event) = sub.buffer.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        if sub.closed {
+            return Poll::Ready(None);
+        }
+        sub.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<Ev> Drop for GhostSubscription<Ev> {
+    fn drop(&mut self) {
+        let mut sub = self.0.lock().unwrap();
+        sub.closed = true;
+        sub.buffer.clear();
+    }
+}
+
+impl<Ev> std::fmt::Debug for GhostSubscription<Ev> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostSubscription")
+            .field("event", &std::any::type_name::<Ev>())
+            .field("missed", &self.missed())
+            .finish()
+    }
+}
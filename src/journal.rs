@@ -0,0 +1,368 @@
+use crate::*;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+
+/// Append-only storage for the events of an event-sourced actor,
+/// see `GhostEventActor`. Appends happen on the actor task,
+/// so `append()` should be quick.
+pub trait GhostJournal: 'static + Send + Sync {
+    /// Durably append the encoded events of a single command.
+    /// Either all entries are appended, or, on error, none are.
+    fn append(&self, entries: &[Vec<u8>]) -> Result<(), GhostError>;
+
+    /// All appended entries, oldest first.
+    fn replay(&self) -> Result<Vec<Vec<u8>>, GhostError>;
+}
+
+/// Journal stored as a file of records, one per command, each framed with
+/// its length and a checksum. Records are never rewritten. A record torn
+/// by a crash mid-append is discarded as a whole, both on replay and
+/// before the next append, so a command is never half replayed.
+#[derive(Debug)]
+pub struct GhostFileJournal {
+    path: std::path::PathBuf,
+    file: Mutex<Option<std::fs::File>>,
+}
+
+impl GhostFileJournal {
+    /// Journal to `path`. The file is created on the first append.
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            file: Mutex::new(None),
+        }
+    }
+
+    /// The path the journal is stored at.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn open(&self) -> std::io::Result<std::fs::File> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        // drop any torn record, so we don't append after it
+        let data = std::fs::read(&self.path)?;
+        let (_, complete) = parse_records(&data);
+        if complete < data.len() {
+            tracing::warn!(path = ?self.path, "discarding incomplete journal record");
+            file.set_len(complete as u64)?;
+        }
+        Ok(file)
+    }
+}
+
+/// Record header: payload length (u32 LE), then payload checksum (u64 LE).
+const HEADER_LEN: usize = 12;
+
+/// FNV-1a, enough to tell a torn or garbled record from a whole one.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Frame the entries of one command as a single record.
+fn encode_record(entries: &[Vec<u8>]) -> Result<Vec<u8>, GhostError> {
+    let too_large = || GhostError::from("journal record is too large");
+    let mut payload = Vec::new();
+    for entry in entries {
+        let len = u32::try_from(entry.len()).map_err(|_| too_large())?;
+        payload.extend_from_slice(&len.to_le_bytes());
+        payload.extend_from_slice(entry);
+    }
+    let len = u32::try_from(payload.len()).map_err(|_| too_large())?;
+
+    let mut record = Vec::with_capacity(HEADER_LEN + payload.len());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&checksum(&payload).to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+/// Decode the entries of all whole records in `data`, stopping at the
+/// first torn or garbled one. Also returns the length of those records.
+fn parse_records(data: &[u8]) -> (Vec<Vec<u8>>, usize) {
+    fn split_u32(data: &[u8]) -> Option<(usize, &[u8])> {
+        let (len, rest) = data.split_first_chunk::<4>()?;
+        Some((u32::from_le_bytes(*len) as usize, rest))
+    }
+
+    fn parse_payload(mut payload: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let mut entries = Vec::new();
+        while !payload.is_empty() {
+            let (len, rest) = split_u32(payload)?;
+            if rest.len() < len {
+                return None;
+            }
+            let (entry, rest) = rest.split_at(len);
+            entries.push(entry.to_vec());
+            payload = rest;
+        }
+        Some(entries)
+    }
+
+    let mut entries = Vec::new();
+    let mut complete = 0;
+    loop {
+        let rest = &data[complete..];
+        let record = split_u32(rest).and_then(|(len, rest)| {
+            let (sum, rest) = rest.split_first_chunk::<8>()?;
+            let payload = rest.get(..len)?;
+            if u64::from_le_bytes(*sum) != checksum(payload) {
+                return None;
+            }
+            Some((len, parse_payload(payload)?))
+        });
+        match record {
+            Some((len, record)) => {
+                entries.extend(record);
+                complete += HEADER_LEN + len;
+            }
+            None => return (entries, complete),
+        }
+    }
+}
+
+impl GhostJournal for GhostFileJournal {
+    fn append(&self, entries: &[Vec<u8>]) -> Result<(), GhostError> {
+        use std::io::Write;
+
+        let record = encode_record(entries)?;
+
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            *file = Some(self.open().map_err(GhostError::other)?);
+        }
+        let file = file.as_mut().unwrap();
+
+        let len = file.metadata().map_err(GhostError::other)?.len();
+        let res = file.write_all(&record).and_then(|_| file.sync_data());
+        if let Err(e) = res {
+            // don't leave part of the record behind, if we can help it,
+            // anything left over is discarded as torn on replay
+            let _ = file.set_len(len);
+            return Err(GhostError::other(e));
+        }
+        Ok(())
+    }
+
+    fn replay(&self) -> Result<Vec<Vec<u8>>, GhostError> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(GhostError::other(e)),
+        };
+        Ok(parse_records(&data).0)
+    }
+}
+
+/// Journal kept in memory. Clones share the same entries,
+/// so a clone can be used to replay an actor, e.g. in tests.
+#[derive(Debug, Clone, Default)]
+pub struct GhostMemoryJournal(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl GhostMemoryJournal {
+    /// An empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GhostJournal for GhostMemoryJournal {
+    fn append(&self, entries: &[Vec<u8>]) -> Result<(), GhostError> {
+        self.0.lock().unwrap().extend_from_slice(entries);
+        Ok(())
+    }
+
+    fn replay(&self) -> Result<Vec<Vec<u8>>, GhostError> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+/// State for an event-sourced actor, see `GhostEventActor`.
+/// Commands are turned into events by `handle()`, and only events
+/// change the state, through `apply()`.
+pub trait GhostEventSourced: 'static + Send {
+    /// Requests to change the state.
+    type Command: 'static + Send;
+
+    /// Recorded changes to the state.
+    type Event: 'static + Send + serde::Serialize + serde::de::DeserializeOwned;
+
+    /// Error returned when a command is rejected.
+    type Error: 'static + From<GhostError> + Send;
+
+    /// Validate `command` against the current state,
+    /// returning the events it produces.
+    fn handle(
+        &self,
+        command: Self::Command,
+    ) -> Result<Vec<Self::Event>, Self::Error>;
+
+    /// Apply `event` to the state. This is also how the state is rebuilt
+    /// from the journal, so it must not fail, or depend on anything
+    /// but the state and the event. Events are journaled before they are
+    /// applied, so if this panics, the event will panic again on replay,
+    /// and `GhostEventActor::new()` will return an error until the journal
+    /// is repaired.
+    fn apply(&mut self, event: &Self::Event);
+}
+
+/// Handle to an event-sourced actor. Every event is appended to a
+/// `GhostJournal` before it is applied to the state, and the state is
+/// rebuilt from the journal on startup, giving a full record of every
+/// change. The actor is an ordinary `GhostActor` underneath.
+///
+/// ```
+/// # use ghost_actor::*;
+/// # #[tokio::main]
+/// # async fn main() {
+/// #[derive(Default)]
+/// struct Account(u64);
+///
+/// impl GhostEventSourced for Account {
+///     type Command = u64;
+///     type Event = u64;
+///     type Error = GhostError;
+///
+///     fn handle(&self, deposit: u64) -> Result<Vec<u64>, GhostError> {
+///         Ok(vec![deposit])
+///     }
+///
+///     fn apply(&mut self, deposit: &u64) {
+///         self.0 += deposit;
+///     }
+/// }
+///
+/// let journal = GhostMemoryJournal::new();
+///
+/// let (account, driver) = GhostEventActor::new(
+///     GhostConfig::default(),
+///     journal.clone(),
+///     Account::default(),
+/// )
+/// .unwrap();
+/// tokio::task::spawn(driver);
+/// account.command(5).await.unwrap();
+/// account.shutdown_graceful().await.unwrap();
+///
+/// // the state is rebuilt from the journal
+/// let (account, driver) =
+///     GhostEventActor::new(GhostConfig::default(), journal, Account::default())
+///         .unwrap();
+/// tokio::task::spawn(driver);
+/// assert_eq!(5, account.query(|a| <Result<u64, GhostError>>::Ok(a.0)).await.unwrap());
+/// # }
+/// ```
+pub struct GhostEventActor<T: GhostEventSourced> {
+    actor: GhostActor<T>,
+    journal: Arc<dyn GhostJournal>,
+}
+
+impl<T: GhostEventSourced> GhostEventActor<T> {
+    /// Create a new event-sourced actor with config, replaying the
+    /// events in `journal` onto the `initial` state. Returns an error
+    /// if the journal could not be read, an entry could not be decoded,
+    /// or applying an entry panicked.
+    pub fn new<J: GhostJournal>(
+        config: GhostConfig,
+        journal: J,
+        initial: T,
+    ) -> Result<(Self, GhostDriver), GhostError> {
+        let mut t = initial;
+        for (index, entry) in journal.replay()?.into_iter().enumerate() {
+            let event: T::Event =
+                serde_json::from_slice(&entry).map_err(GhostError::other)?;
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                t.apply(&event)
+            }))
+            .map_err(|e| {
+                GhostError::from(format!(
+                    "replaying journal entry {} panicked: {}",
+                    index,
+                    panic_message(&*e),
+                ))
+            })?;
+        }
+
+        let (actor, driver) = GhostActor::new_config(config, t);
+        Ok((
+            Self {
+                actor,
+                journal: Arc::new(journal),
+            },
+            driver,
+        ))
+    }
+
+    /// Submit a command. Its events are journaled together, then
+    /// applied, and returned once all have been. If any event cannot be
+    /// encoded or journaled, none of them are applied.
+    pub fn command(
+        &self,
+        command: T::Command,
+    ) -> GhostFuture<Vec<T::Event>, T::Error> {
+        let journal = self.journal.clone();
+        self.actor.invoke(move |t| {
+            let events = t.handle(command)?;
+            let entries = events
+                .iter()
+                .map(serde_json::to_vec)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(GhostError::other)?;
+            journal.append(&entries)?;
+            for event in &events {
+                t.apply(event);
+            }
+            Ok(events)
+        })
+    }
+
+    /// Push state read logic onto actor queue for processing.
+    /// The state can only be changed through `command()`.
+    pub fn query<R, E, F>(&self, query: F) -> GhostFuture<R, E>
+    where
+        R: 'static + Send,
+        E: 'static + From<GhostError> + Send,
+        F: FnOnce(&T) -> Result<R, E> + 'static + Send,
+    {
+        self.actor.invoke(move |t| query(t))
+    }
+
+    /// Returns `true` if the channel is still connected to the actor task.
+    pub fn is_active(&self) -> bool {
+        self.actor.is_active()
+    }
+
+    /// Close the channel to the actor task,
+    /// see `GhostActor::shutdown()`.
+    pub fn shutdown(&self) {
+        self.actor.shutdown();
+    }
+
+    /// Close the channel to the actor task, resolving once all pending
+    /// commands have been processed, see `GhostActor::shutdown_graceful()`.
+    pub fn shutdown_graceful(&self) -> GhostFuture<(), GhostError> {
+        self.actor.shutdown_graceful()
+    }
+}
+
+impl<T: GhostEventSourced> Clone for GhostEventActor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+            journal: self.journal.clone(),
+        }
+    }
+}
+
+impl<T: GhostEventSourced> std::fmt::Debug for GhostEventActor<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("GhostEventActor").field(&self.actor).finish()
+    }
+}
@@ -199,9 +199,34 @@ pub use driver::*;
 mod future;
 pub use future::*;
 mod config;
+mod mailbox;
+mod timer;
 pub use config::*;
+pub use timer::*;
+mod metrics;
+pub use metrics::*;
+mod read_pool;
+pub use read_pool::*;
+mod lifecycle;
+pub use lifecycle::*;
 mod actor;
 pub use actor::*;
+mod registry;
+pub use registry::*;
+mod stream;
+pub use stream::*;
+mod watch;
+pub use watch::GhostWatch;
+mod events;
+pub use events::*;
+#[cfg(feature = "persistence")]
+mod persistence;
+#[cfg(feature = "persistence")]
+pub use persistence::*;
+#[cfg(feature = "persistence")]
+mod journal;
+#[cfg(feature = "persistence")]
+pub use journal::*;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,87 @@
+/// Optional lifecycle hooks for GhostActor state types.
+/// Construct the actor with `GhostActor::new_lifecycle()` to have the
+/// driver invoke these.
+///
+/// # Example
+///
+/// ```
+/// # use ghost_actor::*;
+/// # #[tokio::main]
+/// # async fn main() {
+/// struct Journal(Vec<String>);
+///
+/// impl GhostLifecycle for Journal {
+///     fn started(&mut self) {
+///         self.0.push("started".to_string());
+///     }
+///
+///     fn stopping(
+///         &mut self,
+///     ) -> dependencies::futures::future::BoxFuture<'_, ()> {
+///         Box::pin(async move {
+///             // flush asynchronously here
+///             self.0.push("stopped".to_string());
+///         })
+///     }
+/// }
+///
+/// let (actor, driver) =
+///     GhostActor::new_lifecycle(GhostConfig::default(), Journal(Vec::new()));
+/// tokio::task::spawn(driver);
+/// actor.shutdown();
+/// # }
+/// ```
+pub trait GhostLifecycle: 'static + Send {
+    /// Called by the driver before the first invocation is processed.
+    /// A panic here is handled according to `GhostConfig::panic_policy`.
+    fn started(&mut self) {}
+
+    /// Called by the driver after the channel has closed and all pending
+    /// invocations have been processed. The driver awaits the returned
+    /// future before completing. This is not called if the actor stops
+    /// because an invocation panicked. A panic here is handled according
+    /// to `GhostConfig::panic_policy`.
+    fn stopping(&mut self) -> futures::future::BoxFuture<'_, ()> {
+        Box::pin(async {})
+    }
+}
+
+/// Called with the state after an invocation, along with the number of
+/// invocations it accounted for.
+pub(crate) type ProcessedHook<T> = Box<dyn FnMut(&T, u64) + 'static + Send>;
+
+/// Called with the final state, after `stopping`.
+pub(crate) type StoppedHook<T> = Box<dyn FnOnce(&T) + 'static + Send>;
+
+/// Lifecycle callbacks the driver invokes on the state.
+pub(crate) struct Hooks<T> {
+    pub(crate) started: fn(&mut T),
+    pub(crate) stopping:
+        for<'a> fn(&'a mut T) -> futures::future::BoxFuture<'a, ()>,
+    pub(crate) processed: Option<ProcessedHook<T>>,
+    pub(crate) stopped: Option<StoppedHook<T>>,
+}
+
+impl<T> Hooks<T> {
+    /// Hooks that do nothing, for plain state types.
+    pub(crate) fn none() -> Self {
+        Self {
+            started: |_| {},
+            stopping: |_| Box::pin(async {}),
+            processed: None,
+            stopped: None,
+        }
+    }
+}
+
+impl<T: GhostLifecycle> Hooks<T> {
+    /// Hooks that forward to the GhostLifecycle implementation.
+    pub(crate) fn lifecycle() -> Self {
+        Self {
+            started: T::started,
+            stopping: T::stopping,
+            processed: None,
+            stopped: None,
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Instant;
+
+/// Why a message could not be queued. The message is handed back.
+pub(crate) enum MailboxError<M> {
+    /// The receiver was dropped or the mailbox was closed.
+    Closed(M),
+
+    /// The mailbox is at capacity, and the policy does not allow waiting.
+    Full(M),
+}
+
+impl<M> MailboxError<M> {
+    /// Drop the undelivered message, converting into a GhostError.
+    /// `closed` maps the closed condition into an appropriate error.
+    pub(crate) fn into_ghost_error<C>(self, closed: C) -> GhostError
+    where
+        C: FnOnce(MailboxClosed) -> GhostError,
+    {
+        match self {
+            Self::Closed(_) => closed(MailboxClosed),
+            Self::Full(_) => GhostErrorKind::MailboxFull.into(),
+        }
+    }
+}
+
+/// The actor is no longer receiving messages.
+#[derive(Debug)]
+pub(crate) struct MailboxClosed;
+
+impl std::fmt::Display for MailboxClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "send failed because receiver is gone")
+    }
+}
+
+impl std::error::Error for MailboxClosed {}
+
+/// A message along with when it was queued, if the mailbox is timestamping.
+pub(crate) struct Queued<M> {
+    pub(crate) msg: M,
+    pub(crate) queued_at: Option<Instant>,
+}
+
+struct Inner<M> {
+    queue: VecDeque<Queued<M>>,
+    closed: bool,
+    senders: usize,
+    recv_waker: Option<Waker>,
+    blocked_seq: u64,
+    blocked: VecDeque<(u64, Waker)>,
+    above_high_water: bool,
+}
+
+impl<M> Inner<M> {
+    fn wake_receiver(&mut self) {
+        if let Some(waker) = self.recv_waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_blocked_sender(&mut self) {
+        if let Some((_, waker)) = self.blocked.pop_front() {
+            waker.wake();
+        }
+    }
+
+    fn wake_all_blocked_senders(&mut self) {
+        for (_, waker) in self.blocked.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The shared multi-producer single-consumer message queue
+/// backing a GhostActor.
+struct Mailbox<M> {
+    inner: Mutex<Inner<M>>,
+    bound: Option<usize>,
+    policy: GhostOverflowPolicy,
+    high_water_mark: Option<usize>,
+    timestamps: bool,
+}
+
+impl<M> Mailbox<M> {
+    /// Attempt to queue a message, applying the overflow policy if full,
+    /// or failing with `MailboxError::Full` if `wait` is set.
+    /// Messages evicted to make room are returned, so they can be
+    /// dropped outside the lock.
+    fn push(
+        &self,
+        msg: M,
+        inner: &mut Inner<M>,
+        wait: bool,
+    ) -> Result<Option<M>, MailboxError<M>> {
+        if inner.closed {
+            return Err(MailboxError::Closed(msg));
+        }
+
+        let mut evicted = None;
+        if self.bound.is_some_and(|bound| inner.queue.len() >= bound) {
+            if wait {
+                return Err(MailboxError::Full(msg));
+            }
+            match self.policy {
+                GhostOverflowPolicy::Block | GhostOverflowPolicy::Reject => {
+                    return Err(MailboxError::Full(msg));
+                }
+                GhostOverflowPolicy::DropOldest => {
+                    evicted = inner.queue.pop_front().map(|q| q.msg);
+                }
+                GhostOverflowPolicy::DropNewest => {
+                    return Ok(Some(msg));
+                }
+            }
+        }
+
+        let queued_at = match self.timestamps {
+            true => Some(Instant::now()),
+            false => None,
+        };
+        inner.queue.push_back(Queued { msg, queued_at });
+        inner.wake_receiver();
+
+        if let Some(mark) = self.high_water_mark {
+            if !inner.above_high_water && inner.queue.len() >= mark {
+                inner.above_high_water = true;
+                tracing::warn!(
+                    len = inner.queue.len(),
+                    high_water_mark = mark,
+                    "GhostActor mailbox reached high-water mark",
+                );
+            }
+        }
+
+        Ok(evicted)
+    }
+
+    /// Take the next queued message, re-arming the high-water warning
+    /// once the queue has drained sufficiently.
+    fn pop(&self, inner: &mut Inner<M>) -> Option<Queued<M>> {
+        let msg = inner.queue.pop_front()?;
+        if let Some(mark) = self.high_water_mark {
+            if inner.queue.len() <= mark / 2 {
+                inner.above_high_water = false;
+            }
+        }
+        Some(msg)
+    }
+}
+
+/// Create a new mailbox as configured.
+pub(crate) fn mailbox<M>(
+    config: &GhostConfig,
+) -> (MailboxSender<M>, MailboxReceiver<M>) {
+    let mailbox = Arc::new(Mailbox {
+        inner: Mutex::new(Inner {
+            queue: VecDeque::new(),
+            closed: false,
+            senders: 1,
+            recv_waker: None,
+            blocked_seq: 0,
+            blocked: VecDeque::new(),
+            above_high_water: false,
+        }),
+        bound: match config.unbounded {
+            true => None,
+            false => Some(config.channel_bound.max(1)),
+        },
+        policy: config.overflow_policy,
+        high_water_mark: config.high_water_mark,
+        timestamps: config.metrics,
+    });
+    (MailboxSender(mailbox.clone()), MailboxReceiver(mailbox))
+}
+
+/// Sending side of a mailbox. The mailbox closes when all
+/// senders are dropped.
+pub(crate) struct MailboxSender<M>(Arc<Mailbox<M>>);
+
+impl<M> MailboxSender<M> {
+    /// Queue a message, waiting for capacity if the overflow policy
+    /// is `GhostOverflowPolicy::Block`.
+    pub(crate) fn send(&self, msg: M) -> SendFuture<'_, M> {
+        SendFuture {
+            mailbox: &self.0,
+            msg: Some(msg),
+            key: None,
+            wait: self.0.policy == GhostOverflowPolicy::Block,
+        }
+    }
+
+    /// Queue a message, waiting for capacity whatever the overflow policy.
+    /// For messages the crate sends on its own behalf, which should
+    /// neither be lost nor rejected.
+    pub(crate) fn send_wait(&self, msg: M) -> SendFuture<'_, M> {
+        SendFuture {
+            mailbox: &self.0,
+            msg: Some(msg),
+            key: None,
+            wait: true,
+        }
+    }
+
+    /// Queue a message without waiting. If the mailbox is at capacity and
+    /// the overflow policy is `GhostOverflowPolicy::Block`, this fails
+    /// with `MailboxError::Full`.
+    pub(crate) fn try_send(&self, msg: M) -> Result<(), MailboxError<M>> {
+        let evicted = {
+            let mut inner = self.0.inner.lock().unwrap();
+            self.0.push(msg, &mut inner, false)?
+        };
+        drop(evicted);
+        Ok(())
+    }
+
+    /// Close the mailbox. Messages already queued will still be received.
+    pub(crate) fn close(&self) {
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.closed = true;
+        inner.wake_receiver();
+        inner.wake_all_blocked_senders();
+    }
+
+    /// The number of messages waiting in the mailbox.
+    pub(crate) fn len(&self) -> usize {
+        self.0.inner.lock().unwrap().queue.len()
+    }
+
+    /// Returns `true` if the mailbox is closed to new messages.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.0.inner.lock().unwrap().closed
+    }
+
+    /// Returns `true` if both senders feed the same receiver.
+    pub(crate) fn same_receiver(&self, o: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &o.0)
+    }
+
+    /// Hash the identity of the receiver this sender feeds.
+    pub(crate) fn hash_receiver<H: std::hash::Hasher>(&self, hasher: &mut H) {
+        std::hash::Hash::hash(&Arc::as_ptr(&self.0), hasher);
+    }
+}
+
+impl<M> Clone for MailboxSender<M> {
+    fn clone(&self) -> Self {
+        self.0.inner.lock().unwrap().senders += 1;
+        Self(self.0.clone())
+    }
+}
+
+impl<M> Drop for MailboxSender<M> {
+    fn drop(&mut self) {
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.senders -= 1;
+        if inner.senders == 0 {
+            inner.wake_receiver();
+        }
+    }
+}
+
+/// Future returned from `MailboxSender::send()`.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub(crate) struct SendFuture<'a, M> {
+    mailbox: &'a Mailbox<M>,
+    msg: Option<M>,
+    key: Option<u64>,
+    /// wait for capacity, rather than applying the overflow policy
+    wait: bool,
+}
+
+// we never pin-project into `msg`
+impl<M> Unpin for SendFuture<'_, M> {}
+
+impl<M> std::future::Future for SendFuture<'_, M> {
+    type Output = Result<(), MailboxError<M>>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let msg = match this.msg.take() {
+            None => panic!("SendFuture polled after completion"),
+            Some(msg) => msg,
+        };
+
+        let mut inner = this.mailbox.inner.lock().unwrap();
+        if let Some(key) = this.key.take() {
+            inner.blocked.retain(|(k, _)| *k != key);
+        }
+
+        match this.mailbox.push(msg, &mut inner, this.wait) {
+            Ok(evicted) => {
+                drop(inner);
+                drop(evicted);
+                Poll::Ready(Ok(()))
+            }
+            Err(MailboxError::Full(msg)) if this.wait => {
+                inner.blocked_seq += 1;
+                let key = inner.blocked_seq;
+                inner.blocked.push_back((key, cx.waker().clone()));
+                this.key = Some(key);
+                this.msg = Some(msg);
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl<M> Drop for SendFuture<'_, M> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let mut inner = self.mailbox.inner.lock().unwrap();
+            let before = inner.blocked.len();
+            inner.blocked.retain(|(k, _)| *k != key);
+            if inner.blocked.len() == before {
+                // we were woken for a free slot we will never use,
+                // pass the wake along to the next blocked sender
+                inner.wake_blocked_sender();
+            }
+        }
+    }
+}
+
+/// Receiving side of a mailbox.
+pub(crate) struct MailboxReceiver<M>(Arc<Mailbox<M>>);
+
+impl<M> futures::stream::Stream for MailboxReceiver<M> {
+    type Item = Queued<M>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut inner = self.0.inner.lock().unwrap();
+        match self.0.pop(&mut inner) {
+            Some(msg) => {
+                inner.wake_blocked_sender();
+                Poll::Ready(Some(msg))
+            }
+            None if inner.closed || inner.senders == 0 => Poll::Ready(None),
+            None => {
+                inner.recv_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<M> Drop for MailboxReceiver<M> {
+    fn drop(&mut self) {
+        let queue = {
+            let mut inner = self.0.inner.lock().unwrap();
+            inner.closed = true;
+            inner.wake_all_blocked_senders();
+            std::mem::take(&mut inner.queue)
+        };
+        // drop any undelivered messages outside the lock
+        drop(queue);
+    }
+}
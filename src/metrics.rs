@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// A point-in-time snapshot of GhostActor metrics,
+/// see `GhostConfig::metrics`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GhostMetrics {
+    /// The number of invocations waiting to be processed.
+    pub queue_depth: usize,
+
+    /// The number of invocations processed so far.
+    /// A batch counts as a single invocation.
+    pub invocations_processed: u64,
+
+    /// The total time processed invocations spent waiting in the queue.
+    pub queued_time: Duration,
+
+    /// The total time the driver spent executing invocations.
+    pub exec_time: Duration,
+
+    /// The number of strong handles to the actor, including any boxed
+    /// handles. This also counts handles held on your behalf: by a
+    /// `GhostBatch` or `GhostStream` until it is dropped, by a pending
+    /// `watch()` registration, and briefly by timers and attached streams
+    /// while they queue an invocation.
+    pub live_handles: usize,
+}
+
+impl GhostMetrics {
+    /// The mean time an invocation spent waiting in the queue.
+    pub fn mean_queued_time(&self) -> Duration {
+        mean(self.queued_time, self.invocations_processed)
+    }
+
+    /// The mean time spent executing an invocation.
+    pub fn mean_exec_time(&self) -> Duration {
+        mean(self.exec_time, self.invocations_processed)
+    }
+}
+
+fn mean(total: Duration, count: u64) -> Duration {
+    match count {
+        0 => Duration::ZERO,
+        count => {
+            Duration::from_nanos((total.as_nanos() / count as u128) as u64)
+        }
+    }
+}
+
+/// Counters updated by the driver, only allocated if metrics are enabled.
+#[derive(Default)]
+pub(crate) struct MetricsState {
+    backlog: AtomicUsize,
+    processed: AtomicU64,
+    queued_nanos: AtomicU64,
+    exec_nanos: AtomicU64,
+}
+
+impl MetricsState {
+    /// Track invocations the driver has pulled from the mailbox,
+    /// but not yet processed.
+    pub(crate) fn set_backlog(&self, len: usize) {
+        self.backlog.store(len, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_queued(&self, queued: Duration) {
+        self.queued_nanos
+            .fetch_add(queued.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_exec(&self, count: u64, exec: Duration) {
+        self.processed.fetch_add(count, Ordering::Relaxed);
+        self.exec_nanos
+            .fetch_add(exec.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(
+        &self,
+        mailbox_len: usize,
+        live_handles: usize,
+    ) -> GhostMetrics {
+        GhostMetrics {
+            queue_depth: mailbox_len + self.backlog.load(Ordering::Relaxed),
+            invocations_processed: self.processed.load(Ordering::Relaxed),
+            queued_time: Duration::from_nanos(
+                self.queued_nanos.load(Ordering::Relaxed),
+            ),
+            exec_time: Duration::from_nanos(
+                self.exec_nanos.load(Ordering::Relaxed),
+            ),
+            live_handles,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::*;
+use std::sync::{Arc, Mutex};
+
+/// Storage backend for actor state snapshots, see `GhostPersistence`.
+/// Snapshots are taken on the actor task, so `save()` should be quick.
+pub trait GhostSnapshotStore: 'static + Send + Sync {
+    /// Replace the stored snapshot with `snapshot`.
+    fn save(&self, snapshot: &[u8]) -> Result<(), GhostError>;
+
+    /// The most recently saved snapshot, if any.
+    fn load(&self) -> Result<Option<Vec<u8>>, GhostError>;
+}
+
+/// Stores snapshots in a single file. Each snapshot is written to a
+/// temporary file alongside it first, then renamed over the original,
+/// so a crash mid-write never leaves a partial snapshot. On unix, the
+/// directory is synced after the rename, so the snapshot is durable once
+/// `save()` returns.
+#[derive(Debug, Clone)]
+pub struct GhostFileSnapshotStore {
+    path: std::path::PathBuf,
+}
+
+impl GhostFileSnapshotStore {
+    /// Store snapshots at `path`.
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The path snapshots are stored at.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn tmp_path(&self) -> std::path::PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_owned();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+}
+
+impl GhostSnapshotStore for GhostFileSnapshotStore {
+    fn save(&self, snapshot: &[u8]) -> Result<(), GhostError> {
+        use std::io::Write;
+
+        let tmp = self.tmp_path();
+        let mut file =
+            std::fs::File::create(&tmp).map_err(GhostError::other)?;
+        file.write_all(snapshot).map_err(GhostError::other)?;
+        file.sync_all().map_err(GhostError::other)?;
+        std::fs::rename(&tmp, &self.path).map_err(GhostError::other)?;
+
+        // the rename itself is only durable once the directory is synced,
+        // directories cannot be opened as files on windows
+        #[cfg(unix)]
+        {
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => std::path::Path::new("."),
+            };
+            std::fs::File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .map_err(GhostError::other)?;
+        }
+
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, GhostError> {
+        match std::fs::read(&self.path) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(GhostError::other(e)),
+        }
+    }
+}
+
+/// Stores snapshots in memory. Clones share the same snapshot,
+/// so a clone can be used to restore an actor, e.g. in tests.
+#[derive(Debug, Clone, Default)]
+pub struct GhostMemorySnapshotStore(Arc<Mutex<Option<Vec<u8>>>>);
+
+impl GhostMemorySnapshotStore {
+    /// An empty snapshot store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GhostSnapshotStore for GhostMemorySnapshotStore {
+    fn save(&self, snapshot: &[u8]) -> Result<(), GhostError> {
+        *self.0.lock().unwrap() = Some(snapshot.to_vec());
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, GhostError> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+/// When to snapshot the state of a persistent actor,
+/// see `GhostActor::new_persistent()`.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct GhostPersistence {
+    /// Where snapshots are stored.
+    pub store: Arc<dyn GhostSnapshotStore>,
+
+    /// Snapshot after this many invocations have been processed.
+    /// Defaults to `None`.
+    pub every_invocations: Option<u64>,
+
+    /// Snapshot at this interval, as measured by `GhostConfig::timer`.
+    /// Defaults to `None`.
+    pub interval: Option<std::time::Duration>,
+
+    /// Snapshot the final state when the actor shuts down.
+    /// Defaults to `true`.
+    pub on_shutdown: bool,
+}
+
+impl GhostPersistence {
+    /// Snapshot to `store`, with the default triggers.
+    pub fn new<S: GhostSnapshotStore>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            every_invocations: None,
+            interval: None,
+            on_shutdown: true,
+        }
+    }
+}
+
+impl std::fmt::Debug for GhostPersistence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostPersistence")
+            .field("every_invocations", &self.every_invocations)
+            .field("interval", &self.interval)
+            .field("on_shutdown", &self.on_shutdown)
+            .finish()
+    }
+}
+
+/// Serialize and save `t`, tracing any failure,
+/// the actor keeps running either way.
+fn snapshot<T: serde::Serialize>(store: &dyn GhostSnapshotStore, t: &T) {
+    let res = serde_json::to_vec(t)
+        .map_err(GhostError::other)
+        .and_then(|snapshot| store.save(&snapshot));
+    if let Err(e) = res {
+        tracing::error!(?e, "GhostActor failed to snapshot state");
+    }
+}
+
+impl<T> GhostActor<T>
+where
+    T: 'static + Send + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Create a new GhostActor with config and initial state,
+    /// snapshotting the state to `persistence.store` as configured.
+    ///
+    /// ```
+    /// # use ghost_actor::*;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let store = GhostMemorySnapshotStore::new();
+    ///
+    /// let (actor, driver) = GhostActor::new_persistent(
+    ///     GhostConfig::default(),
+    ///     GhostPersistence::new(store.clone()),
+    ///     0_u32,
+    /// );
+    /// tokio::task::spawn(driver);
+    /// actor.invoke(|i| { *i += 1; <Result<(), GhostError>>::Ok(()) })
+    ///     .await
+    ///     .unwrap();
+    /// actor.shutdown_graceful().await.unwrap();
+    ///
+    /// // pick up where we left off
+    /// let (actor, driver) = GhostActor::restore(
+    ///     GhostConfig::default(),
+    ///     GhostPersistence::new(store),
+    ///     || 0_u32,
+    /// )
+    /// .unwrap();
+    /// tokio::task::spawn(driver);
+    /// assert_eq!(1, actor.invoke(|i| <Result<u32, GhostError>>::Ok(*i)).await.unwrap());
+    /// # }
+    /// ```
+    pub fn new_persistent(
+        config: GhostConfig,
+        persistence: GhostPersistence,
+        t: T,
+    ) -> (Self, GhostDriver) {
+        let GhostPersistence {
+            store,
+            every_invocations,
+            interval,
+            on_shutdown,
+        } = persistence;
+
+        let mut hooks = Hooks::none();
+        if let Some(every) = every_invocations {
+            let store = store.clone();
+            let every = every.max(1);
+            let mut since = 0;
+            hooks.processed = Some(Box::new(move |t: &T, processed| {
+                since += processed;
+                if since >= every {
+                    since = 0;
+                    snapshot(&*store, t);
+                }
+            }));
+        }
+        if on_shutdown {
+            let store = store.clone();
+            hooks.stopped = Some(Box::new(move |t: &T| snapshot(&*store, t)));
+        }
+
+        let (actor, driver) = Self::new_hooks(config, t, hooks);
+
+        if let Some(interval) = interval {
+            // stops with the actor, no need to hold on to the handle
+            let _ = actor.invoke_every(interval, move |t| snapshot(&*store, t));
+        }
+
+        (actor, driver.into())
+    }
+
+    /// Create a new persistent GhostActor as in `new_persistent()`,
+    /// with the state rebuilt from the latest snapshot in
+    /// `persistence.store`, or built by `default` if there is none.
+    /// Returns an error if the snapshot could not be loaded or decoded.
+    pub fn restore<F>(
+        config: GhostConfig,
+        persistence: GhostPersistence,
+        default: F,
+    ) -> Result<(Self, GhostDriver), GhostError>
+    where
+        F: FnOnce() -> T,
+    {
+        let t = match persistence.store.load()? {
+            Some(snapshot) => {
+                serde_json::from_slice(&snapshot).map_err(GhostError::other)?
+            }
+            None => default(),
+        };
+        Ok(Self::new_persistent(config, persistence, t))
+    }
+}
@@ -0,0 +1,57 @@
+/// A group of `invoke_read()` invocations, borrowing the actor state,
+/// handed to a `GhostReadPool` to run.
+pub type GhostReadJob<'a> = Box<dyn FnOnce() + 'a + Send>;
+
+/// Runs groups of adjacent `invoke_read()` invocations concurrently.
+/// Set through `GhostConfig::read_pool`.
+pub trait GhostReadPool: 'static + Send + Sync {
+    /// Run every job, concurrently where possible, returning once all
+    /// have completed. The jobs borrow the actor state, so this must block
+    /// until then, as `rayon::scope()` does. Jobs never panic, any panic
+    /// in a read is caught and reported to its caller.
+    fn run(&self, jobs: Vec<GhostReadJob<'_>>);
+}
+
+/// A GhostReadPool backed by rayon, so reads run on threads that are
+/// spawned once, and shared by every actor using the pool.
+///
+/// ```
+/// # use ghost_actor::*;
+/// let mut config = GhostConfig::default();
+/// config.read_concurrency = 4;
+/// config.read_pool = Some(std::sync::Arc::new(GhostRayonReadPool::new()));
+/// ```
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Default)]
+pub struct GhostRayonReadPool(Option<std::sync::Arc<rayon::ThreadPool>>);
+
+#[cfg(feature = "rayon")]
+impl GhostRayonReadPool {
+    /// Run reads on the global rayon pool, one thread per CPU.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run reads on `pool`.
+    pub fn with_pool(pool: std::sync::Arc<rayon::ThreadPool>) -> Self {
+        Self(Some(pool))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl GhostReadPool for GhostRayonReadPool {
+    fn run(&self, jobs: Vec<GhostReadJob<'_>>) {
+        fn spawn_all<'a>(
+            scope: &rayon::Scope<'a>,
+            jobs: Vec<GhostReadJob<'a>>,
+        ) {
+            for job in jobs {
+                scope.spawn(move |_| job());
+            }
+        }
+        match &self.0 {
+            Some(pool) => pool.scope(|scope| spawn_all(scope, jobs)),
+            None => rayon::scope(|scope| spawn_all(scope, jobs)),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::*;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+struct Entry {
+    /// identifies the actor, so a stopped actor only ever
+    /// removes its own registration
+    id: usize,
+    typed: Box<dyn Any + Send + Sync>,
+    boxed: BoxWeakGhostActor,
+}
+
+type Entries<K> = Mutex<HashMap<K, Entry>>;
+
+/// A registry for looking up actors by key, either as `GhostActor<T>` or
+/// as type-erased `BoxGhostActor`. Keys can be strings or any typed key.
+///
+/// The registry only holds weak handles, so it does not keep actors alive.
+/// Entries are removed automatically once an actor's driver completes.
+///
+/// ```
+/// # use ghost_actor::*;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let (actor, driver) = GhostActor::new(42_u32);
+/// tokio::task::spawn(driver);
+///
+/// let registry = GhostRegistry::<String>::global();
+/// registry.register("answer".to_string(), &actor);
+///
+/// let found: GhostActor<u32> = registry.get(&"answer".to_string()).unwrap();
+/// assert_eq!(42, found.invoke(|a| <Result<u32, GhostError>>::Ok(*a)).await.unwrap());
+///
+/// actor.shutdown_graceful().await.unwrap();
+/// assert!(registry.get_boxed(&"answer".to_string()).is_none());
+/// # }
+/// ```
+pub struct GhostRegistry<K = String> {
+    entries: Arc<Entries<K>>,
+}
+
+impl<K> Clone for GhostRegistry<K> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<K> Default for GhostRegistry<K> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<K> std::fmt::Debug for GhostRegistry<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostRegistry")
+            .field("key", &std::any::type_name::<K>())
+            .field("len", &self.entries.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl<K> GhostRegistry<K>
+where
+    K: 'static + Eq + Hash + Clone + Send + Sync,
+{
+    /// Create a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry for keys of type `K`.
+    pub fn global() -> &'static Self {
+        type Globals = HashMap<TypeId, &'static (dyn Any + Send + Sync)>;
+        static GLOBALS: OnceLock<Mutex<Globals>> = OnceLock::new();
+
+        let mut globals = GLOBALS.get_or_init(Default::default).lock().unwrap();
+        let global = *globals
+            .entry(TypeId::of::<K>())
+            .or_insert_with(|| Box::leak(Box::new(Self::new())));
+        global
+            .downcast_ref()
+            .expect("registry stored under the wrong TypeId")
+    }
+
+    /// Register `actor` under `key`, returning any live actor
+    /// previously registered under that key.
+    pub fn register<T: 'static + Send>(
+        &self,
+        key: K,
+        actor: &GhostActor<T>,
+    ) -> Option<BoxGhostActor> {
+        let shared = actor.shared();
+        let id = Arc::as_ptr(shared) as usize;
+        let weak = actor.downgrade();
+        let entry = Entry {
+            id,
+            boxed: weak.to_boxed(),
+            typed: Box::new(weak),
+        };
+
+        let prev = self.entries.lock().unwrap().insert(key.clone(), entry);
+
+        // if we are re-registering the same actor,
+        // its exit hook is already in place
+        if prev.as_ref().is_none_or(|e| e.id != id) {
+            // the hook only holds a weak reference, the registry may be
+            // dropped before the actor stops
+            let entries: Weak<Entries<K>> = Arc::downgrade(&self.entries);
+            shared.on_exit(Box::new(move || {
+                if let Some(entries) = entries.upgrade() {
+                    let mut entries = entries.lock().unwrap();
+                    if entries.get(&key).is_some_and(|e| e.id == id) {
+                        entries.remove(&key);
+                    }
+                }
+            }));
+        }
+
+        prev.and_then(|e| e.boxed.upgrade())
+            .filter(|a| a.is_active())
+    }
+
+    /// Remove the actor registered under `key`, returning it if still live.
+    pub fn unregister(&self, key: &K) -> Option<BoxGhostActor> {
+        let entry = self.entries.lock().unwrap().remove(key)?;
+        entry.boxed.upgrade().filter(|a| a.is_active())
+    }
+
+    /// Look up the actor registered under `key`.
+    /// Returns `None` if there is no such actor, it is no longer active,
+    /// or its state is not of type `T`.
+    pub fn get<T: 'static + Send>(&self, key: &K) -> Option<GhostActor<T>> {
+        let entries = self.entries.lock().unwrap();
+        let weak: &WeakGhostActor<T> =
+            entries.get(key)?.typed.downcast_ref()?;
+        weak.upgrade().filter(|a| a.is_active())
+    }
+
+    /// Look up the actor registered under `key`, type-erased.
+    /// Returns `None` if there is no such actor, or it is no longer active.
+    pub fn get_boxed(&self, key: &K) -> Option<BoxGhostActor> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key)?.boxed.upgrade().filter(|a| a.is_active())
+    }
+
+    /// Find the key `actor` is registered under, if any.
+    pub fn key_of(&self, actor: &BoxGhostActor) -> Option<K> {
+        self.actors()
+            .into_iter()
+            .find(|(_, a)| a == actor)
+            .map(|(k, _)| k)
+    }
+
+    /// Enumerate all registered actors that are still active.
+    pub fn actors(&self) -> Vec<(K, BoxGhostActor)> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter_map(|(k, e)| {
+                let actor = e.boxed.upgrade().filter(|a| a.is_active())?;
+                Some((k.clone(), actor))
+            })
+            .collect()
+    }
+
+    /// Enumerate the keys of all registered actors that are still active.
+    pub fn keys(&self) -> Vec<K> {
+        self.actors().into_iter().map(|(k, _)| k).collect()
+    }
+}
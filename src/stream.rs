@@ -0,0 +1,113 @@
+use crate::*;
+
+/// Handle to a stream attached with `GhostActor::attach_stream()`.
+/// Dropping the handle does not detach the stream, use `cancel()`.
+/// Streams are detached automatically when the actor shuts down.
+#[derive(Debug, Clone)]
+pub struct GhostStreamHandle(pub(crate) futures::future::AbortHandle);
+
+impl GhostStreamHandle {
+    /// Stop pulling items from the stream.
+    /// An item already being handled is still processed.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    /// Returns `true` if `cancel()` has been called.
+    pub fn is_canceled(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// Result stream for `GhostActor::invoke_stream()`.
+#[must_use = "streams do nothing unless polled"]
+pub struct GhostStream<I, E>(futures::stream::BoxStream<'static, Result<I, E>>)
+where
+    E: 'static + From<GhostError> + Send;
+
+impl<I, E> GhostStream<I, E>
+where
+    E: 'static + From<GhostError> + Send,
+{
+    /// Wrap another compatible stream in a GhostStream.
+    #[inline]
+    pub fn new<S>(s: S) -> Self
+    where
+        S: 'static + futures::stream::Stream<Item = Result<I, E>> + Send,
+    {
+        Self(futures::stream::StreamExt::boxed(s))
+    }
+}
+
+impl<I, E> futures::stream::Stream for GhostStream<I, E>
+where
+    E: 'static + From<GhostError> + Send,
+{
+    type Item = Result<I, E>;
+
+    #[inline]
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        futures::stream::Stream::poll_next(self.0.as_mut(), cx)
+    }
+}
+
+/// Bounded buffer handed to `GhostActor::invoke_stream()` logic,
+/// collecting the next chunk of items for the caller.
+pub struct GhostStreamSink<I> {
+    items: std::collections::VecDeque<I>,
+    capacity: usize,
+    finished: bool,
+}
+
+impl<I> GhostStreamSink<I> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            items: std::collections::VecDeque::with_capacity(capacity),
+            capacity,
+            finished: false,
+        }
+    }
+
+    /// Queue an item for the caller.
+    /// If the sink is full, the item is handed back.
+    pub fn push(&mut self, item: I) -> Result<(), I> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.items.push_back(item);
+        Ok(())
+    }
+
+    /// The number of items that can still be pushed in this chunk.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.items.len()
+    }
+
+    /// Returns `true` if no more items can be pushed in this chunk.
+    pub fn is_full(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Mark the stream as complete. The logic will not be invoked again,
+    /// and the stream ends once the caller has received the pushed items.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    pub(crate) fn into_parts(self) -> (std::collections::VecDeque<I>, bool) {
+        (self.items, self.finished)
+    }
+}
+
+impl<I> std::fmt::Debug for GhostStreamSink<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostStreamSink")
+            .field("len", &self.items.len())
+            .field("capacity", &self.capacity)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
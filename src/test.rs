@@ -1,4 +1,5 @@
 use crate::*;
+use std::sync::Arc;
 use tracing::Instrument;
 
 #[tokio::test]
@@ -173,3 +174,1697 @@ async fn full_actor_workflow_test() {
     let fruit: Box<dyn Fruit> = Box::new(banana);
     assert_eq!("ate 2 bananas", &fruit.eat().await.unwrap());
 }
+
+#[tokio::test]
+async fn invoke_timeout_without_driver() {
+    observability::test_run().ok();
+
+    // never spawn the driver, so the invocation can never complete
+    let (actor, _driver) = GhostActor::new(42_u8);
+
+    let err = actor
+        .invoke_timeout(std::time::Duration::from_millis(10), |i| {
+            <Result<u8, GhostError>>::Ok(*i)
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Timeout), err.kind());
+
+    let err = actor
+        .to_boxed()
+        .invoke_timeout(std::time::Duration::from_millis(10), |i: &mut u8| {
+            <Result<u8, GhostError>>::Ok(*i)
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Timeout), err.kind());
+}
+
+#[tokio::test]
+async fn config_default_invoke_timeout() {
+    observability::test_run().ok();
+
+    let config = GhostConfig {
+        invoke_timeout: Some(std::time::Duration::from_millis(10)),
+        ..Default::default()
+    };
+    let (actor, driver) = GhostActor::new_config(config, 42_u8);
+
+    let err = actor
+        .invoke(|i| <Result<u8, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Timeout), err.kind());
+
+    tokio::task::spawn(driver);
+
+    assert_eq!(
+        42,
+        actor
+            .invoke(|i| <Result<u8, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    // an explicit timeout overrides the configured one, boxed or not
+    let slow = |i: &mut u8| {
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        <Result<u8, GhostError>>::Ok(*i)
+    };
+    let timeout = std::time::Duration::from_secs(1);
+    assert_eq!(42, actor.invoke_timeout(timeout, slow).await.unwrap());
+    assert_eq!(
+        42,
+        actor
+            .to_boxed()
+            .invoke_timeout(timeout, slow)
+            .await
+            .unwrap()
+    );
+
+    // deadlines too far out to represent simply never fire
+    assert_eq!(
+        42,
+        actor
+            .invoke_timeout(std::time::Duration::MAX, |i| {
+                <Result<u8, GhostError>>::Ok(*i)
+            })
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn cancelable_invoke_skipped_on_drop() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(0_u32);
+
+    // queue up invocations before the driver is running, then drop them
+    for _ in 0..3 {
+        let mut fut = actor.invoke_cancelable(|i| {
+            *i += 1;
+            <Result<(), GhostError>>::Ok(())
+        });
+        futures::future::poll_fn(|cx| {
+            assert!(std::future::Future::poll(
+                std::pin::Pin::new(&mut fut),
+                cx
+            )
+            .is_pending());
+            std::task::Poll::Ready(())
+        })
+        .await;
+    }
+
+    // this one is not cancelable, so it still runs once dropped
+    let mut fut = actor.invoke(|i| {
+        *i += 10;
+        <Result<(), GhostError>>::Ok(())
+    });
+    futures::future::poll_fn(|cx| {
+        assert!(std::future::Future::poll(std::pin::Pin::new(&mut fut), cx)
+            .is_pending());
+        std::task::Poll::Ready(())
+    })
+    .await;
+    drop(fut);
+
+    tokio::task::spawn(driver);
+
+    assert_eq!(
+        10,
+        actor
+            .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn panic_policy_isolation() {
+    observability::test_run().ok();
+
+    for policy in [
+        GhostPanicPolicy::Continue,
+        GhostPanicPolicy::Poison,
+        GhostPanicPolicy::Shutdown,
+    ] {
+        let config = GhostConfig {
+            panic_policy: policy,
+            ..Default::default()
+        };
+        let (actor, driver) = GhostActor::new_config(config, 42_u8);
+        let driver = tokio::task::spawn(driver);
+
+        let err = actor
+            .invoke(|_| -> Result<(), GhostError> { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            Some(&GhostErrorKind::Panicked("boom".to_string())),
+            err.kind(),
+        );
+
+        let res = actor.invoke(|i| <Result<u8, GhostError>>::Ok(*i)).await;
+        match policy {
+            GhostPanicPolicy::Continue => {
+                assert_eq!(42, res.unwrap());
+                assert!(actor.is_active());
+            }
+            _ => {
+                assert_eq!(
+                    Some(&GhostErrorKind::Poisoned),
+                    res.unwrap_err().kind()
+                );
+                assert!(!actor.is_active());
+            }
+        }
+
+        if policy == GhostPanicPolicy::Shutdown {
+            // the driver task exits cleanly rather than unwinding
+            driver.await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn supervised_restart() {
+    observability::test_run().ok();
+
+    let policy = GhostRestartPolicy {
+        max_restarts: 1,
+        ..Default::default()
+    };
+    let (actor, driver) =
+        GhostActor::new_supervised(GhostConfig::default(), policy, || 42_u8);
+    tokio::task::spawn(driver);
+    let clone = actor.clone();
+
+    actor
+        .invoke(|i| {
+            *i = 7;
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+
+    let err = actor
+        .invoke(|_| -> Result<(), GhostError> { panic!("boom") })
+        .await
+        .unwrap_err();
+    assert_eq!(
+        Some(&GhostErrorKind::Panicked("boom".to_string())),
+        err.kind(),
+    );
+
+    // existing clones see the freshly rebuilt state
+    assert_eq!(
+        42,
+        clone
+            .invoke(|i| <Result<u8, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    // the second panic exceeds the restart policy
+    let _ = actor
+        .invoke(|_| -> Result<(), GhostError> { panic!("boom") })
+        .await;
+    let err = clone
+        .invoke(|i| <Result<u8, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Poisoned), err.kind());
+}
+
+#[tokio::test]
+async fn lifecycle_hooks() {
+    observability::test_run().ok();
+
+    struct State {
+        log: Vec<&'static str>,
+        done: Option<futures::channel::oneshot::Sender<Vec<&'static str>>>,
+    }
+
+    impl GhostLifecycle for State {
+        fn started(&mut self) {
+            self.log.push("started");
+        }
+
+        fn stopping(&mut self) -> futures::future::BoxFuture<'_, ()> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                self.log.push("stopping");
+                let log = self.log.clone();
+                let _ = self.done.take().unwrap().send(log);
+            })
+        }
+    }
+
+    let (done, done_recv) = futures::channel::oneshot::channel();
+    let (actor, driver) = GhostActor::new_lifecycle(
+        GhostConfig::default(),
+        State {
+            log: Vec::new(),
+            done: Some(done),
+        },
+    );
+    tokio::task::spawn(driver);
+
+    actor
+        .invoke(|s| {
+            s.log.push("invoke");
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+    actor.shutdown();
+
+    assert_eq!(
+        vec!["started", "invoke", "stopping"],
+        done_recv.await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn lifecycle_hook_panics() {
+    observability::test_run().ok();
+
+    #[derive(Clone, Copy)]
+    struct Panicky {
+        started: bool,
+        stopping: bool,
+    }
+
+    impl GhostLifecycle for Panicky {
+        fn started(&mut self) {
+            if self.started {
+                panic!("started panicked");
+            }
+        }
+
+        fn stopping(&mut self) -> futures::future::BoxFuture<'_, ()> {
+            Box::pin(async move {
+                if self.stopping {
+                    panic!("stopping panicked");
+                }
+            })
+        }
+    }
+
+    let config = |panic_policy| GhostConfig {
+        panic_policy,
+        ..Default::default()
+    };
+    let get = |actor: GhostActor<Panicky>| async move {
+        actor
+            .invoke(|_| <Result<(), GhostError>>::Ok(()))
+            .await
+            .map_err(|e| e.kind().cloned())
+    };
+
+    // a panicking started hook is subject to the panic policy
+    let started = Panicky {
+        started: true,
+        stopping: false,
+    };
+    for policy in [GhostPanicPolicy::Poison, GhostPanicPolicy::Shutdown] {
+        let (actor, driver) =
+            GhostActor::new_lifecycle(config(policy), started);
+        tokio::task::spawn(driver);
+        assert_eq!(Err(Some(GhostErrorKind::Poisoned)), get(actor).await);
+    }
+    let (actor, driver) =
+        GhostActor::new_lifecycle(config(GhostPanicPolicy::Continue), started);
+    tokio::task::spawn(driver);
+    assert_eq!(Ok(()), get(actor).await);
+
+    // so is a panicking stopping hook, which poisons the final state
+    let stopping = Panicky {
+        started: false,
+        stopping: true,
+    };
+    let (actor, driver) = GhostActor::new_hooks(
+        config(GhostPanicPolicy::Shutdown),
+        stopping,
+        Hooks::lifecycle(),
+    );
+    let driver = tokio::task::spawn(driver);
+    actor.shutdown();
+    let err = driver.await.unwrap().map(|_| ()).unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Poisoned), err.kind());
+
+    let (actor, driver) = GhostActor::new_hooks(
+        config(GhostPanicPolicy::Continue),
+        stopping,
+        Hooks::lifecycle(),
+    );
+    let driver = tokio::task::spawn(driver);
+    actor.shutdown();
+    assert!(driver.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn state_driver_returns_final_state() {
+    observability::test_run().ok();
+
+    let (actor, driver) =
+        GhostActor::new_config_state(GhostConfig::default(), vec![1_u8]);
+    let driver = tokio::task::spawn(driver);
+
+    for i in 2..=5 {
+        actor
+            .invoke(move |v| {
+                v.push(i);
+                <Result<(), GhostError>>::Ok(())
+            })
+            .await
+            .unwrap();
+    }
+    actor.shutdown();
+
+    assert_eq!(vec![1, 2, 3, 4, 5], driver.await.unwrap().unwrap());
+}
+
+#[tokio::test]
+async fn shutdown_graceful_drains() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(0_u32);
+
+    let pending = (0..5)
+        .map(|_| {
+            actor.invoke(|i| {
+                *i += 1;
+                <Result<u32, GhostError>>::Ok(*i)
+            })
+        })
+        .collect::<Vec<_>>();
+    let pending = tokio::task::spawn(futures::future::join_all(pending));
+    tokio::task::yield_now().await;
+
+    tokio::task::spawn(driver);
+    actor.shutdown_graceful().await.unwrap();
+
+    let results = pending.await.unwrap();
+    assert!(results.into_iter().all(|r| r.is_ok()));
+    assert!(!actor.is_active());
+}
+
+#[tokio::test]
+async fn shutdown_now_fails_pending() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(0_u32);
+
+    let pending = actor.invoke(|i| <Result<u32, GhostError>>::Ok(*i));
+    let pending = tokio::task::spawn(pending);
+    tokio::task::yield_now().await;
+
+    let shutdown = actor.shutdown_now();
+    tokio::task::spawn(driver);
+    shutdown.await.unwrap();
+
+    let err = pending.await.unwrap().unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Shutdown), err.kind());
+
+    let err = actor
+        .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Shutdown), err.kind());
+}
+
+#[tokio::test]
+async fn shutdown_graceful_timeout_aborts() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(0_u32);
+
+    // the driver will be blocked, keep it off the tokio runtime
+    std::thread::spawn(move || futures::executor::block_on(driver));
+
+    let (unblock, blocked) = std::sync::mpsc::channel::<()>();
+    let first = tokio::task::spawn(actor.invoke(move |_| {
+        // hold up the driver until the test releases us
+        blocked.recv().unwrap();
+        <Result<(), GhostError>>::Ok(())
+    }));
+    tokio::task::yield_now().await;
+    let second =
+        tokio::task::spawn(actor.invoke(|i| <Result<u32, GhostError>>::Ok(*i)));
+    tokio::task::yield_now().await;
+
+    let shutdown = tokio::task::spawn(
+        actor.shutdown_graceful_timeout(std::time::Duration::from_millis(10)),
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    unblock.send(()).unwrap();
+
+    shutdown.await.unwrap().unwrap();
+    first.await.unwrap().unwrap();
+    let err = second.await.unwrap().unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Shutdown), err.kind());
+}
+
+#[tokio::test]
+async fn weak_handles() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(42_u8);
+    let driver = tokio::task::spawn(driver);
+
+    let weak = actor.downgrade();
+    let box_weak = actor.to_boxed().downgrade();
+    assert_eq!(weak, actor.downgrade());
+    assert_eq!(box_weak, actor.downgrade().to_boxed());
+
+    fn hash<H: std::hash::Hash>(h: &H) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        h.hash(&mut hasher);
+        std::hash::Hasher::finish(&hasher)
+    }
+    assert_eq!(hash(&weak), hash(&actor.downgrade()));
+
+    let (other, _) = GhostActor::new(42_u8);
+    assert_ne!(weak, other.downgrade());
+
+    assert_eq!(actor, weak.upgrade().unwrap());
+    assert_eq!(actor.to_boxed(), box_weak.upgrade().unwrap());
+    assert_eq!(hash(&actor.to_boxed()), hash(&box_weak.upgrade().unwrap()));
+    assert_eq!(
+        42,
+        box_weak
+            .upgrade()
+            .unwrap()
+            .invoke(|i: &mut u8| <Result<u8, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    // weak handles do not keep the actor alive
+    drop(actor);
+    driver.await.unwrap();
+    assert!(weak.upgrade().is_none());
+    assert!(box_weak.upgrade().is_none());
+}
+
+#[tokio::test]
+async fn driver_yields_to_other_tasks() {
+    observability::test_run().ok();
+
+    let config = GhostConfig {
+        max_batch_size: 8,
+        yield_after_invokes: Some(2),
+        ..Default::default()
+    };
+    let (actor, driver) = GhostActor::new_config(config, ());
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    // queue up a batch of invocations before the driver starts
+    let pending = (0..6)
+        .map(|_| {
+            let log = log.clone();
+            actor.invoke(move |_| {
+                log.lock().unwrap().push("invoke");
+                <Result<(), GhostError>>::Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    let pending = tokio::task::spawn(futures::future::join_all(pending));
+    tokio::task::yield_now().await;
+
+    // another task on our single threaded runtime, scheduled right
+    // behind the driver, records when it gets to run
+    tokio::task::spawn(driver);
+    let other_log = log.clone();
+    let other = tokio::task::spawn(async move {
+        other_log.lock().unwrap().push("other");
+    });
+
+    pending.await.unwrap();
+    other.await.unwrap();
+
+    // without yielding, "other" could only run after all six invokes
+    let log = log.lock().unwrap();
+    assert_eq!(7, log.len());
+    assert_ne!("other", log[6]);
+}
+
+#[tokio::test]
+async fn invoke_exclusive_holds_state() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(Vec::new());
+    tokio::task::spawn(driver);
+
+    let exclusive = actor.invoke_exclusive(|log: &mut Vec<&'static str>| {
+        Box::pin(async move {
+            log.push("start");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            log.push("end");
+            <Result<(), GhostError>>::Ok(())
+        })
+    });
+    let exclusive = tokio::task::spawn(exclusive);
+    tokio::task::yield_now().await;
+
+    actor
+        .invoke(|log| {
+            log.push("other");
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+    exclusive.await.unwrap().unwrap();
+
+    assert_eq!(
+        vec!["start", "end", "other"],
+        actor
+            .invoke(|log| <Result<_, GhostError>>::Ok(log.clone()))
+            .await
+            .unwrap()
+    );
+
+    let err = actor
+        .invoke_exclusive(|_| {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                if true {
+                    panic!("boom");
+                }
+                <Result<(), GhostError>>::Ok(())
+            })
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(
+        Some(&GhostErrorKind::Panicked("boom".to_string())),
+        err.kind(),
+    );
+}
+
+/// Runs each job on its own scoped thread.
+struct ScopedReadPool;
+
+impl GhostReadPool for ScopedReadPool {
+    fn run(&self, jobs: Vec<GhostReadJob<'_>>) {
+        std::thread::scope(|scope| {
+            for job in jobs {
+                scope.spawn(job);
+            }
+        });
+    }
+}
+
+#[tokio::test]
+async fn invoke_read_concurrent() {
+    observability::test_run().ok();
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The most reads seen running at once.
+    async fn max_running(read_pool: Option<Arc<dyn GhostReadPool>>) -> usize {
+        let config = GhostConfig {
+            read_concurrency: 4,
+            read_pool,
+            ..Default::default()
+        };
+        let (actor, driver) = GhostActor::new_config(config, 42_u8);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        // queue up the reads before the driver starts, so they are adjacent
+        let reads = (0..4)
+            .map(|_| {
+                let running = running.clone();
+                let max_running = max_running.clone();
+                actor.invoke_read(move |i| {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    <Result<u8, GhostError>>::Ok(*i)
+                })
+            })
+            .collect::<Vec<_>>();
+        let reads = tokio::task::spawn(futures::future::join_all(reads));
+        tokio::task::yield_now().await;
+        tokio::task::spawn(driver);
+
+        for r in reads.await.unwrap() {
+            assert_eq!(42, r.unwrap());
+        }
+
+        let err = actor
+            .invoke_read(|_| -> Result<(), GhostError> { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            Some(&GhostErrorKind::Panicked("boom".to_string())),
+            err.kind(),
+        );
+
+        max_running.load(Ordering::SeqCst)
+    }
+
+    // without a pool, reads run one at a time
+    assert_eq!(1, max_running(None).await);
+    assert!(max_running(Some(Arc::new(ScopedReadPool))).await > 1);
+    #[cfg(feature = "rayon")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let pool = GhostRayonReadPool::with_pool(Arc::new(pool));
+        assert!(max_running(Some(Arc::new(pool))).await > 1);
+    }
+}
+
+#[tokio::test]
+async fn notify_without_response() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(0_u32);
+    tokio::task::spawn(driver);
+    let boxed = actor.to_boxed();
+
+    actor.notify(|i| *i += 1).await.unwrap();
+    actor.try_notify(|i| *i += 10).unwrap();
+    boxed.notify(|i: &mut u32| *i += 100).await.unwrap();
+    boxed.try_notify(|i: &mut u32| *i += 1000).unwrap();
+
+    // logic for the wrong concrete type is skipped
+    boxed.try_notify(|i: &mut u8| *i += 1).unwrap();
+
+    assert_eq!(
+        1111,
+        actor
+            .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    actor.shutdown_graceful().await.unwrap();
+    assert!(actor.try_notify(|i| *i += 1).is_err());
+    assert!(boxed.notify(|i: &mut u32| *i += 1).await.is_err());
+}
+
+#[tokio::test]
+async fn mailbox_overflow_policies() {
+    observability::test_run().ok();
+
+    let config = |overflow_policy| GhostConfig {
+        channel_bound: 1,
+        overflow_policy,
+        ..Default::default()
+    };
+
+    // block: try_invoke fails fast, invoke waits for the driver
+    let (actor, driver) =
+        GhostActor::new_config(config(GhostOverflowPolicy::Block), 0_u32);
+    let first = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i));
+    let err = actor
+        .try_invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::MailboxFull), err.kind());
+    let err = actor.try_notify(|i| *i += 1).unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::MailboxFull), err.kind());
+    let err = actor
+        .to_boxed()
+        .try_invoke(|i: &mut u32| <Result<u32, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::MailboxFull), err.kind());
+    let second = tokio::task::spawn(
+        actor.invoke(|i| <Result<u32, GhostError>>::Ok(*i + 1)),
+    );
+    tokio::task::spawn(driver);
+    assert_eq!(0, first.await.unwrap());
+    assert_eq!(1, second.await.unwrap().unwrap());
+
+    // reject: invoke fails too
+    let (actor, _driver) =
+        GhostActor::new_config(config(GhostOverflowPolicy::Reject), 0_u32);
+    actor.try_notify(|i| *i += 1).unwrap();
+    let err = actor
+        .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::MailboxFull), err.kind());
+
+    // drop oldest: the evicted caller is told
+    let (actor, driver) =
+        GhostActor::new_config(config(GhostOverflowPolicy::DropOldest), 0_u32);
+    let first = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i));
+    let second = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i + 1));
+    tokio::task::spawn(driver);
+    let err = first.await.unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Dropped), err.kind());
+    assert_eq!(1, second.await.unwrap());
+
+    // drop newest: the incoming caller is told
+    let (actor, driver) =
+        GhostActor::new_config(config(GhostOverflowPolicy::DropNewest), 0_u32);
+    let first = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i));
+    let second = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i + 1));
+    tokio::task::spawn(driver);
+    assert_eq!(0, first.await.unwrap());
+    let err = second.await.unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Dropped), err.kind());
+}
+
+#[tokio::test]
+async fn unbounded_mailbox() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new_config(
+        GhostConfig {
+            unbounded: true,
+            high_water_mark: Some(16),
+            ..Default::default()
+        },
+        0_u32,
+    );
+
+    // callers never wait, even with nobody processing the mailbox
+    for _ in 0..1000 {
+        actor.try_notify(|i| *i += 1).unwrap();
+    }
+    let count = actor.try_invoke(|i| <Result<u32, GhostError>>::Ok(*i));
+
+    tokio::task::spawn(driver);
+    assert_eq!(1000, count.await.unwrap());
+}
+
+#[tokio::test]
+async fn batch_invocations() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(Vec::<&'static str>::new());
+
+    let mut batch = actor.batch();
+    let a = batch.invoke(|v| {
+        v.push("a");
+        <Result<usize, GhostError>>::Ok(v.len())
+    });
+    let b = batch.invoke(|v| {
+        v.push("b");
+        <Result<String, GhostError>>::Ok(v.join(","))
+    });
+    assert_eq!(2, batch.len());
+
+    // queued ahead of the batch, but the batch runs as one entry
+    let other = actor.invoke(|v| {
+        v.push("other");
+        <Result<(), GhostError>>::Ok(())
+    });
+    let sent = batch.send();
+    let other = tokio::task::spawn(other);
+    tokio::task::yield_now().await;
+    sent.await.unwrap();
+    tokio::task::spawn(driver);
+
+    other.await.unwrap().unwrap();
+    let a = a.await.unwrap();
+    let b = b.await.unwrap();
+    assert!(
+        (a == 1 && b == "a,b") || (a == 2 && b == "other,a,b"),
+        "{} {}",
+        a,
+        b,
+    );
+
+    let results = actor
+        .invoke_batch((0..3).map(|n| {
+            move |v: &mut Vec<&'static str>| {
+                <Result<usize, GhostError>>::Ok(v.len() + n)
+            }
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![3, 4, 5],
+        results.into_iter().map(Result::unwrap).collect::<Vec<_>>()
+    );
+
+    let results = actor
+        .to_boxed()
+        .invoke_batch(vec![|v: &mut Vec<&'static str>| {
+            v.clear();
+            <Result<usize, GhostError>>::Ok(v.len())
+        }])
+        .await
+        .unwrap();
+    assert_eq!(0, *results[0].as_ref().unwrap());
+
+    // a panic only fails its own closure, and those after it
+    let config = GhostConfig {
+        panic_policy: GhostPanicPolicy::Continue,
+        ..Default::default()
+    };
+    let (other, driver) = GhostActor::new_config(config, 0_u32);
+    tokio::task::spawn(driver);
+    let results = other
+        .to_boxed()
+        .invoke_batch((0..3).map(|n| {
+            move |i: &mut u32| {
+                if n == 1 {
+                    panic!("batch panicked");
+                }
+                *i += 1;
+                <Result<u32, GhostError>>::Ok(*i)
+            }
+        }))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.map_err(|e| e.kind().cloned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Ok(1),
+            Err(Some(GhostErrorKind::Panicked("batch panicked".to_string()))),
+            Err(Some(GhostErrorKind::Dropped)),
+        ],
+        results
+    );
+
+    // an unsent batch reports its invocations as dropped
+    let mut batch = actor.batch();
+    let dropped = batch.invoke(|_| <Result<(), GhostError>>::Ok(()));
+    drop(batch);
+    let err = dropped.await.unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Dropped), err.kind());
+}
+
+#[tokio::test]
+async fn actor_metrics() {
+    observability::test_run().ok();
+
+    let (actor, _driver) = GhostActor::new(0_u32);
+    assert!(actor.metrics().is_none());
+
+    let (actor, driver) = GhostActor::new_config(
+        GhostConfig {
+            metrics: true,
+            ..Default::default()
+        },
+        0_u32,
+    );
+    let boxed = actor.to_boxed();
+
+    for _ in 0..3 {
+        actor.try_notify(|i| *i += 1).unwrap();
+    }
+    let metrics = boxed.metrics().unwrap();
+    assert_eq!(3, metrics.queue_depth);
+    assert_eq!(0, metrics.invocations_processed);
+    assert_eq!(2, metrics.live_handles);
+
+    tokio::task::spawn(driver);
+    actor
+        .invoke(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+    // let the driver finish its bookkeeping
+    actor.shutdown_graceful().await.unwrap();
+
+    let metrics = actor.metrics().unwrap();
+    assert_eq!(0, metrics.queue_depth);
+    assert_eq!(4, metrics.invocations_processed);
+    assert!(metrics.queued_time > std::time::Duration::ZERO);
+    assert!(metrics.exec_time >= std::time::Duration::from_millis(10));
+    assert!(metrics.mean_exec_time() < metrics.exec_time);
+
+    drop(boxed);
+    assert_eq!(1, actor.metrics().unwrap().live_handles);
+}
+
+#[tokio::test]
+async fn named_actor() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new_config(
+        GhostConfig {
+            name: Some("counter-7".to_string()),
+            labels: vec![("shard", "7")],
+            ..Default::default()
+        },
+        0_u32,
+    );
+    tokio::task::spawn(driver);
+
+    assert_eq!(Some("counter-7"), actor.name());
+    assert!(format!("{:?}", actor).contains("counter-7"));
+    assert!(format!("{:?}", actor.to_boxed()).contains("counter-7"));
+
+    let span = tracing::info_span!("caller");
+    let r = actor
+        .invoke(|i| {
+            tracing::info!("in invocation");
+            <Result<u32, GhostError>>::Ok(*i + 1)
+        })
+        .instrument(span)
+        .await
+        .unwrap();
+    assert_eq!(1, r);
+}
+
+#[tokio::test]
+async fn registry_lookup_and_removal() {
+    observability::test_run().ok();
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Key {
+        A,
+        B,
+    }
+
+    let registry = GhostRegistry::<Key>::global();
+
+    let (a, driver) = GhostActor::new(1_u32);
+    tokio::task::spawn(driver);
+    let (b, driver) = GhostActor::new("b".to_string());
+    tokio::task::spawn(driver);
+
+    assert!(registry.register(Key::A, &a).is_none());
+    assert!(registry.register(Key::B, &b).is_none());
+
+    // re-registering is idempotent
+    assert_eq!(a.to_boxed(), registry.register(Key::A, &a).unwrap());
+
+    let found: GhostActor<u32> = registry.get(&Key::A).unwrap();
+    assert_eq!(a, found);
+    assert!(registry.get::<u32>(&Key::B).is_none());
+    assert_eq!(b.to_boxed(), registry.get_boxed(&Key::B).unwrap());
+    assert_eq!(Some(Key::B), registry.key_of(&b.to_boxed()));
+
+    let mut keys = registry.keys();
+    keys.sort_by_key(|k| format!("{:?}", k));
+    assert_eq!(vec![Key::A, Key::B], keys);
+
+    // replacing a registration is not undone when the old actor stops
+    let (a2, driver) = GhostActor::new(2_u32);
+    tokio::task::spawn(driver);
+    assert_eq!(a.to_boxed(), registry.register(Key::A, &a2).unwrap());
+    a.shutdown_graceful().await.unwrap();
+    assert_eq!(a2, registry.get::<u32>(&Key::A).unwrap());
+
+    // stopped actors are removed
+    a2.shutdown_graceful().await.unwrap();
+    assert!(registry.get_boxed(&Key::A).is_none());
+
+    // the registry does not keep actors alive
+    drop(found);
+    drop(b);
+    assert!(registry.keys().is_empty());
+}
+
+#[tokio::test]
+async fn timer_invocations() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(Vec::<&'static str>::new());
+    tokio::task::spawn(driver);
+
+    let ms = std::time::Duration::from_millis;
+    actor.invoke_after(ms(20), |v| v.push("after"));
+    let canceled = actor.invoke_after(ms(20), |v| v.push("canceled"));
+    canceled.cancel();
+    assert!(canceled.is_canceled());
+    let ticker = actor.invoke_every(ms(5), |v| v.push("tick"));
+
+    tokio::time::sleep(ms(100)).await;
+    ticker.cancel();
+    let log = actor
+        .invoke(|v| <Result<_, GhostError>>::Ok(std::mem::take(v)))
+        .await
+        .unwrap();
+    assert!(log.contains(&"after"));
+    assert!(!log.contains(&"canceled"));
+    assert!(log.iter().filter(|s| **s == "tick").count() > 2);
+
+    // no more ticks after cancel
+    tokio::time::sleep(ms(20)).await;
+    assert!(actor
+        .invoke(|v| <Result<_, GhostError>>::Ok(v.is_empty()))
+        .await
+        .unwrap());
+
+    // a panicking tick stops the timer, but not the actor
+    let config = GhostConfig {
+        panic_policy: GhostPanicPolicy::Continue,
+        ..Default::default()
+    };
+    let (other, driver) = GhostActor::new_config(config, 0_u32);
+    tokio::task::spawn(driver);
+    other.invoke_every(ms(5), |ticks| {
+        *ticks += 1;
+        if *ticks == 2 {
+            panic!("tick panicked");
+        }
+    });
+    tokio::time::sleep(ms(50)).await;
+    assert_eq!(
+        2,
+        other
+            .invoke(|t| <Result<u32, GhostError>>::Ok(*t))
+            .await
+            .unwrap()
+    );
+
+    // timers stop when the actor shuts down
+    let guard = Arc::new(());
+    let ticker_guard = guard.clone();
+    actor.invoke_every(ms(5), move |_| {
+        let _ = &ticker_guard;
+    });
+    assert_eq!(2, Arc::strong_count(&guard));
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(1, Arc::strong_count(&guard));
+}
+
+#[tokio::test]
+async fn timer_invocations_wait_for_room() {
+    observability::test_run().ok();
+    use futures::future::FutureExt;
+
+    /// A timer under which the deadlines set before `fire` is sent pass,
+    /// and later ones never do.
+    struct Manual(
+        futures::future::Shared<futures::channel::oneshot::Receiver<()>>,
+    );
+
+    impl GhostTimer for Manual {
+        fn sleep(
+            &self,
+            _dur: std::time::Duration,
+        ) -> futures::future::BoxFuture<'static, ()> {
+            if self.0.peek().is_some() {
+                return Box::pin(futures::future::pending());
+            }
+            let fired = self.0.clone();
+            Box::pin(async move {
+                let _ = fired.await;
+            })
+        }
+    }
+
+    let (fire, fired) = futures::channel::oneshot::channel();
+    let config = GhostConfig {
+        channel_bound: 1,
+        overflow_policy: GhostOverflowPolicy::Reject,
+        timer: Arc::new(Manual(fired.shared())),
+        ..Default::default()
+    };
+    let (actor, driver) = GhostActor::new_config(config, ());
+    tokio::task::spawn(driver);
+
+    // logged outside the actor, as the ticks keep its mailbox busy
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let push = |s: &'static str| {
+        let log = log.clone();
+        move |_: &mut ()| log.lock().unwrap().push(s)
+    };
+
+    let ms = std::time::Duration::from_millis;
+    actor.invoke_after(ms(5), push("after"));
+    let ticker = actor.invoke_every(ms(5), push("tick"));
+    // let the driver start the timers
+    tokio::task::yield_now().await;
+
+    // the deadlines pass while the mailbox is full
+    actor.notify(push("filler")).await.unwrap();
+    assert_eq!(
+        Some(&GhostErrorKind::MailboxFull),
+        actor.notify(|_| ()).await.unwrap_err().kind()
+    );
+    fire.send(()).unwrap();
+
+    // the driver polls the timers before taking the filler,
+    // but they wait for room rather than being rejected
+    tokio::time::sleep(ms(20)).await;
+    ticker.cancel();
+    let log = std::mem::take(&mut *log.lock().unwrap());
+    assert_eq!("filler", log[0]);
+    assert!(log.contains(&"after"));
+    assert!(log.contains(&"tick"));
+    actor.shutdown_graceful().await.unwrap();
+}
+
+#[tokio::test]
+async fn configured_timer_for_timeouts() {
+    observability::test_run().ok();
+
+    /// A timer under which every deadline has already passed.
+    struct Expired;
+
+    impl GhostTimer for Expired {
+        fn sleep(
+            &self,
+            _dur: std::time::Duration,
+        ) -> futures::future::BoxFuture<'static, ()> {
+            Box::pin(async {})
+        }
+    }
+
+    let config = GhostConfig {
+        timer: Arc::new(Expired),
+        ..Default::default()
+    };
+    // never spawn the driver, so only the timer can resolve invocations
+    let (actor, _driver) = GhostActor::new_config(config, 42_u8);
+    let hour = std::time::Duration::from_secs(3600);
+
+    let err = actor
+        .invoke_timeout(hour, |i| <Result<u8, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Timeout), err.kind());
+
+    let err = actor
+        .to_boxed()
+        .invoke_timeout(hour, |i: &mut u8| <Result<u8, GhostError>>::Ok(*i))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(&GhostErrorKind::Timeout), err.kind());
+}
+
+#[tokio::test]
+async fn attached_streams() {
+    observability::test_run().ok();
+
+    let (actor, driver) = GhostActor::new(Vec::<u32>::new());
+    tokio::task::spawn(driver);
+
+    // items interleave with other invocations
+    let (mut send, recv) = futures::channel::mpsc::channel(8);
+    actor.attach_stream(recv, |v, n| v.push(n));
+    use futures::sink::SinkExt;
+    send.send(1).await.unwrap();
+    send.send(2).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    actor
+        .invoke(|v| {
+            v.push(100);
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+    send.send(3).await.unwrap();
+    drop(send);
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert_eq!(
+        vec![1, 2, 100, 3],
+        actor
+            .invoke(|v| <Result<_, GhostError>>::Ok(v.clone()))
+            .await
+            .unwrap()
+    );
+
+    // streams can be detached
+    let (mut send, recv) = futures::channel::mpsc::channel(8);
+    let handle = actor.attach_stream(recv, |v, n| v.push(n));
+    handle.cancel();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert!(send.send(4).await.is_err());
+
+    // a panicking stream is detached, without taking the actor with it
+    let config = GhostConfig {
+        panic_policy: GhostPanicPolicy::Continue,
+        ..Default::default()
+    };
+    let (other, driver) = GhostActor::new_config(config, 0_u32);
+    tokio::task::spawn(driver);
+    other.attach_stream(
+        futures::stream::poll_fn(|_| -> std::task::Poll<Option<u32>> {
+            panic!("stream panicked")
+        }),
+        |_, _| {},
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert_eq!(
+        0,
+        other
+            .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    // items wait for room, rather than being rejected
+    let config = GhostConfig {
+        channel_bound: 1,
+        overflow_policy: GhostOverflowPolicy::Reject,
+        ..Default::default()
+    };
+    let (other, driver) = GhostActor::new_config(config, 0_u32);
+    tokio::task::spawn(driver);
+    other.attach_stream(futures::stream::iter(1..=10), |sum, n| *sum += n);
+    // full before the driver first polls the stream
+    other.notify(|_| ()).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert_eq!(
+        55,
+        other
+            .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+            .await
+            .unwrap()
+    );
+
+    // streams are detached when the actor shuts down
+    let guard = Arc::new(());
+    let stream_guard = guard.clone();
+    actor.attach_stream(futures::stream::pending::<u32>(), move |_, _| {
+        let _ = &stream_guard;
+    });
+    assert_eq!(2, Arc::strong_count(&guard));
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(1, Arc::strong_count(&guard));
+}
+
+#[tokio::test]
+async fn invoke_stream_chunks() {
+    observability::test_run().ok();
+    use futures::stream::StreamExt;
+
+    let (actor, driver) = GhostActor::new((0..25).collect::<Vec<u32>>());
+    tokio::task::spawn(driver);
+
+    let chunks = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let chunk_count = chunks.clone();
+    let mut offset = 0;
+    let mut stream = actor.invoke_stream(10, move |peers, sink| {
+        chunk_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        while offset < peers.len() && sink.push(peers[offset]).is_ok() {
+            offset += 1;
+        }
+        if offset == peers.len() {
+            sink.finish();
+        }
+        <Result<(), GhostError>>::Ok(())
+    });
+
+    // chunks are only produced as the caller consumes
+    assert_eq!(Some(0), stream.next().await.map(Result::unwrap));
+    assert_eq!(1, chunks.load(std::sync::atomic::Ordering::SeqCst));
+
+    // other invocations can run between chunks
+    actor
+        .invoke(|peers| {
+            peers.push(25);
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+
+    let rest = stream.map(Result::unwrap).collect::<Vec<_>>().await;
+    assert_eq!((1..26).collect::<Vec<_>>(), rest);
+    assert_eq!(3, chunks.load(std::sync::atomic::Ordering::SeqCst));
+
+    // errors end the stream, after the items pushed before them
+    let stream = actor.invoke_stream(10, |_, sink| {
+        sink.push(1).unwrap();
+        Err(GhostError::from("page failed"))
+    });
+    let results = stream.collect::<Vec<_>>().await;
+    assert_eq!(2, results.len());
+    assert_eq!(1, *results[0].as_ref().unwrap());
+    assert!(results[1].is_err());
+
+    // logic that makes no progress ends the stream, rather than spinning
+    let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let call_count = calls.clone();
+    let stream =
+        actor.invoke_stream(10, move |_, _: &mut GhostStreamSink<u32>| {
+            call_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            <Result<(), GhostError>>::Ok(())
+        });
+    let results = stream.collect::<Vec<_>>().await;
+    assert_eq!(1, results.len());
+    assert!(results[0].is_err());
+    assert_eq!(1, calls.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn watch_state_changes() {
+    observability::test_run().ok();
+    use futures::stream::StreamExt;
+
+    let (actor, driver) = GhostActor::new(0_u32);
+    tokio::task::spawn(driver);
+
+    let add = |n: u32| {
+        actor.invoke(move |i| {
+            *i += n;
+            <Result<(), GhostError>>::Ok(())
+        })
+    };
+
+    let mut tens = actor.watch(|i| *i / 10);
+    assert_eq!(Some(0), tens.next().await);
+
+    // unchanged values are not yielded
+    add(1).await.unwrap();
+    add(12).await.unwrap();
+    assert_eq!(Some(1), tens.next().await);
+
+    // only the latest value is kept for slow watchers
+    add(10).await.unwrap();
+    add(10).await.unwrap();
+    // watchers are evaluated before the next batch is processed
+    add(0).await.unwrap();
+    assert_eq!(Some(3), tens.next().await);
+
+    // dropped watchers are cleaned up, without disturbing others
+    let dropped = actor.watch(|i| *i);
+    drop(dropped);
+    add(10).await.unwrap();
+    assert_eq!(Some(4), tens.next().await);
+
+    // the stream ends when the actor shuts down
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(None, tens.next().await);
+    assert_eq!(None, actor.watch(|i| *i).next().await);
+
+    // watchers are registered even if the mailbox is full
+    let config = GhostConfig {
+        channel_bound: 1,
+        overflow_policy: GhostOverflowPolicy::Reject,
+        ..Default::default()
+    };
+    let (actor, driver) = GhostActor::new_config(config, 7_u32);
+    tokio::task::spawn(driver);
+    actor.notify(|i| *i += 1).await.unwrap();
+    let mut value = actor.watch(|i| *i);
+    // it saw 7 first, but only the latest value is kept
+    assert_eq!(Some(8), value.next().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn emitted_events() {
+    observability::test_run().ok();
+    use futures::stream::StreamExt;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Added(u32);
+
+    let (actor, driver) = GhostActor::new(0_u32);
+    tokio::task::spawn(driver);
+
+    let add = |n: u32| {
+        actor.invoke_ctx(move |i, ctx| {
+            *i += n;
+            ctx.emit(Added(n));
+            // nobody subscribes to strings, this is a no-op
+            ctx.emit("ignored".to_string());
+            <Result<(), GhostError>>::Ok(())
+        })
+    };
+
+    let mut all = actor.subscribe::<Added>();
+    let mut oldest =
+        actor.subscribe_with::<Added>(2, GhostLagPolicy::DropOldest);
+    let mut newest =
+        actor.subscribe_with::<Added>(2, GhostLagPolicy::DropNewest);
+    let mut strict =
+        actor.subscribe_with::<Added>(2, GhostLagPolicy::Unsubscribe);
+    let dropped = actor.subscribe::<Added>();
+    drop(dropped);
+
+    // slow subscribers do not hold up the actor
+    for n in 1..=4 {
+        add(n).await.unwrap();
+    }
+
+    for n in 1..=4 {
+        assert_eq!(Some(Added(n)), all.next().await);
+    }
+    assert_eq!(0, all.missed());
+
+    assert_eq!(Some(Added(3)), oldest.next().await);
+    assert_eq!(Some(Added(4)), oldest.next().await);
+    assert_eq!(2, oldest.missed());
+
+    assert_eq!(Some(Added(1)), newest.next().await);
+    assert_eq!(Some(Added(2)), newest.next().await);
+    assert_eq!(2, newest.missed());
+
+    // lagging subscribers can be unsubscribed, after what was buffered
+    assert_eq!(Some(Added(1)), strict.next().await);
+    assert_eq!(Some(Added(2)), strict.next().await);
+    assert_eq!(None, strict.next().await);
+
+    // subscriptions end when the actor shuts down
+    add(5).await.unwrap();
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(Some(Added(5)), all.next().await);
+    assert_eq!(None, all.next().await);
+    assert_eq!(None, actor.subscribe::<Added>().next().await);
+}
+
+#[cfg(feature = "persistence")]
+#[tokio::test(flavor = "multi_thread")]
+async fn persistent_snapshots() {
+    observability::test_run().ok();
+
+    let store = GhostMemorySnapshotStore::new();
+    let load = || -> Option<u32> {
+        store
+            .load()
+            .unwrap()
+            .map(|s| serde_json::from_slice(&s).unwrap())
+    };
+
+    let mut persistence = GhostPersistence::new(store.clone());
+    persistence.every_invocations = Some(2);
+    let (actor, driver) =
+        GhostActor::new_persistent(GhostConfig::default(), persistence, 0_u32);
+    tokio::task::spawn(driver);
+
+    let add = |n: u32| {
+        actor.invoke(move |i| {
+            *i += n;
+            <Result<(), GhostError>>::Ok(())
+        })
+    };
+
+    add(1).await.unwrap();
+    assert_eq!(None, load());
+    add(1).await.unwrap();
+    // the hook runs after the response is sent
+    add(0).await.unwrap();
+    assert_eq!(Some(2), load());
+
+    // the final state is saved before shutdown completes
+    add(5).await.unwrap();
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(Some(7), load());
+
+    // restore from the latest snapshot in the memory store
+    let (actor, driver) = GhostActor::<u32>::restore(
+        GhostConfig::default(),
+        GhostPersistence::new(store.clone()),
+        || unreachable!("there is a snapshot"),
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+    let restored = actor
+        .invoke(|i| <Result<u32, GhostError>>::Ok(*i))
+        .await
+        .unwrap();
+    assert_eq!(7, restored);
+    actor.shutdown_graceful().await.unwrap();
+
+    // state an invocation panicked in is not snapshotted
+    let mut persistence = GhostPersistence::new(store.clone());
+    persistence.every_invocations = Some(1);
+    persistence.on_shutdown = false;
+    let config = GhostConfig {
+        panic_policy: GhostPanicPolicy::Continue,
+        ..Default::default()
+    };
+    let (actor, driver) =
+        GhostActor::new_persistent(config, persistence, 0_u32);
+    tokio::task::spawn(driver);
+    assert!(actor
+        .invoke(|i| -> Result<(), GhostError> {
+            *i = 99;
+            panic!("half way through")
+        })
+        .await
+        .is_err());
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(Some(7), load());
+
+    // the file store starts from the default, there is no snapshot yet
+    let path = std::env::temp_dir()
+        .join(format!("ghost_actor_snapshot_{}", std::process::id()));
+    let file = GhostFileSnapshotStore::new(&path);
+    let (actor, driver) = GhostActor::restore(
+        GhostConfig::default(),
+        GhostPersistence::new(file.clone()),
+        || 40_u32,
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+    actor
+        .invoke(|i| {
+            *i += 2;
+            <Result<(), GhostError>>::Ok(())
+        })
+        .await
+        .unwrap();
+    actor.shutdown_graceful().await.unwrap();
+    assert_eq!(b"42".to_vec(), file.load().unwrap().unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(None, file.load().unwrap());
+}
+
+#[cfg(feature = "persistence")]
+#[tokio::test(flavor = "multi_thread")]
+async fn event_sourced_journal() {
+    observability::test_run().ok();
+
+    #[derive(Default)]
+    struct Account(u64);
+
+    impl GhostEventSourced for Account {
+        type Command = i64;
+        type Event = (String, u64);
+        type Error = GhostError;
+
+        fn handle(
+            &self,
+            amount: i64,
+        ) -> Result<Vec<(String, u64)>, GhostError> {
+            if amount < 0 && amount.unsigned_abs() > self.0 {
+                return Err("insufficient funds".into());
+            }
+            Ok(vec![match amount < 0 {
+                true => ("withdrew".to_string(), amount.unsigned_abs()),
+                false => ("deposited".to_string(), amount as u64),
+            }])
+        }
+
+        fn apply(&mut self, event: &(String, u64)) {
+            match event.0.as_str() {
+                "withdrew" => self.0 -= event.1,
+                "deposited" => self.0 += event.1,
+                _ => panic!("unknown event"),
+            }
+        }
+    }
+
+    /// A journal that is always out of space.
+    struct FullJournal;
+
+    impl GhostJournal for FullJournal {
+        fn append(&self, _entries: &[Vec<u8>]) -> Result<(), GhostError> {
+            Err("journal is full".into())
+        }
+
+        fn replay(&self) -> Result<Vec<Vec<u8>>, GhostError> {
+            Ok(Vec::new())
+        }
+    }
+
+    let balance = |account: &GhostEventActor<Account>| {
+        account.query(|a| <Result<u64, GhostError>>::Ok(a.0))
+    };
+
+    let journal = GhostMemoryJournal::new();
+    let (account, driver) = GhostEventActor::new(
+        GhostConfig::default(),
+        journal.clone(),
+        Account::default(),
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+
+    assert_eq!(
+        vec![("deposited".to_string(), 10)],
+        account.command(10).await.unwrap(),
+    );
+    account.command(-4).await.unwrap();
+    // rejected commands are not journaled
+    assert!(account.command(-7).await.is_err());
+    assert_eq!(6, balance(&account).await.unwrap());
+    assert_eq!(2, journal.replay().unwrap().len());
+    account.shutdown_graceful().await.unwrap();
+
+    // events that cannot be journaled are not applied
+    let (account, driver) = GhostEventActor::new(
+        GhostConfig::default(),
+        FullJournal,
+        Account::default(),
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+    assert!(account.command(5).await.is_err());
+    assert_eq!(0, balance(&account).await.unwrap());
+
+    // events that panic on replay are reported, rather than unwinding
+    let broken = GhostMemoryJournal::new();
+    broken.append(&[b"[\"stolen\",1]".to_vec()]).unwrap();
+    assert!(GhostEventActor::new(
+        GhostConfig::default(),
+        broken,
+        Account::default(),
+    )
+    .is_err());
+
+    // replay into a file journal, by way of the events
+    let path = std::env::temp_dir()
+        .join(format!("ghost_actor_journal_{}", std::process::id()));
+    let file = GhostFileJournal::new(&path);
+    file.append(&journal.replay().unwrap()).unwrap();
+    drop(file);
+
+    // simulate a crash part way through appending a two event command,
+    // just after the first event was written
+    let whole = std::fs::metadata(&path).unwrap().len();
+    let first = br#"["deposited",10]"#.to_vec();
+    GhostFileJournal::new(&path)
+        .append(&[first.clone(), br#"["deposited",20]"#.to_vec()])
+        .unwrap();
+    let torn = whole + 12 + 4 + first.len() as u64 + 4;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(torn)
+        .unwrap();
+    // neither event is replayed
+    assert_eq!(2, GhostFileJournal::new(&path).replay().unwrap().len());
+
+    let (account, driver) = GhostEventActor::new(
+        GhostConfig::default(),
+        GhostFileJournal::new(&path),
+        Account::default(),
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+    assert_eq!(6, balance(&account).await.unwrap());
+    account.command(1).await.unwrap();
+    account.shutdown_graceful().await.unwrap();
+
+    let (account, driver) = GhostEventActor::new(
+        GhostConfig::default(),
+        GhostFileJournal::new(&path),
+        Account::default(),
+    )
+    .unwrap();
+    tokio::task::spawn(driver);
+    assert_eq!(7, balance(&account).await.unwrap());
+    assert_eq!(3, GhostFileJournal::new(&path).replay().unwrap().len());
+
+    std::fs::remove_file(&path).unwrap();
+}
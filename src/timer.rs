@@ -0,0 +1,217 @@
+use crate::*;
+use futures::task::AtomicWaker;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Shared state between a `Delay` future and the timer thread.
+struct DelayState {
+    fired: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// A pending deadline registered with the timer thread,
+/// the sequence number keeps equal deadlines distinct.
+type Key = (Instant, u64);
+
+#[derive(Default)]
+struct TimerQueue {
+    pending: BTreeMap<Key, Arc<DelayState>>,
+    seq: u64,
+    /// when the timer thread will next wake by itself, if ever
+    wake_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct TimerThread {
+    queue: Mutex<TimerQueue>,
+    cvar: Condvar,
+}
+
+impl TimerThread {
+    /// Access the process-wide timer thread, starting it if needed.
+    /// A dedicated thread keeps us independent of any executor's timer.
+    fn get() -> &'static Arc<TimerThread> {
+        static TIMER: OnceLock<Arc<TimerThread>> = OnceLock::new();
+        TIMER.get_or_init(|| {
+            let timer = Arc::new(TimerThread::default());
+            let thread_timer = timer.clone();
+            std::thread::Builder::new()
+                .name("ghost-actor-timer".to_string())
+                .spawn(move || thread_timer.run())
+                .expect("failed to spawn ghost_actor timer thread");
+            timer
+        })
+    }
+
+    fn register(&self, at: Instant, state: Arc<DelayState>) -> Key {
+        let mut queue = self.queue.lock().unwrap();
+        queue.seq += 1;
+        let key = (at, queue.seq);
+        queue.pending.insert(key, state);
+        // the thread only needs waking if it would sleep past us
+        if queue.wake_at.is_none_or(|wake_at| at < wake_at) {
+            queue.wake_at = Some(at);
+            self.cvar.notify_one();
+        }
+        key
+    }
+
+    fn cancel(&self, key: &Key) {
+        self.queue.lock().unwrap().pending.remove(key);
+    }
+
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = queue.pending.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                let state = entry.remove();
+                state.fired.store(true, Ordering::SeqCst);
+                state.waker.wake();
+            }
+            // entries of dropped Delays are removed, so this may be early
+            queue.wake_at = queue.pending.keys().next().map(|(at, _)| *at);
+            queue = match queue.wake_at {
+                None => self.cvar.wait(queue).unwrap(),
+                Some(at) => self.cvar.wait_timeout(queue, at - now).unwrap().0,
+            };
+        }
+    }
+}
+
+/// Executor agnostic future that resolves after a duration has elapsed.
+/// Dropping it removes its deadline from the timer thread.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub(crate) struct Delay {
+    state: Arc<DelayState>,
+    /// None if the deadline is too far out to represent, it never fires
+    key: Option<Key>,
+}
+
+impl Delay {
+    /// Construct a new Delay future.
+    pub(crate) fn new(dur: Duration) -> Self {
+        let state = Arc::new(DelayState {
+            fired: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        });
+        let key = Instant::now()
+            .checked_add(dur)
+            .map(|at| TimerThread::get().register(at, state.clone()));
+        Self { state, key }
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        // once fired, the timer thread has already removed our entry
+        if let Some(key) = &self.key {
+            if !self.state.fired.load(Ordering::SeqCst) {
+                TimerThread::get().cancel(key);
+            }
+        }
+    }
+}
+
+impl std::future::Future for Delay {
+    type Output = ();
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+    ) -> std::task::Poll<Self::Output> {
+        self.state.waker.register(cx.waker());
+        if self.state.fired.load(Ordering::SeqCst) {
+            std::task::Poll::Ready(())
+        } else {
+            std::task::Poll::Pending
+        }
+    }
+}
+
+/// Executor agnostic source of delays, used for invocation timeouts,
+/// restart backoff, and `invoke_after()` / `invoke_every()`.
+/// Set through `GhostConfig::timer`.
+pub trait GhostTimer: 'static + Send + Sync {
+    /// Returns a future that resolves once `dur` has elapsed.
+    fn sleep(&self, dur: Duration) -> futures::future::BoxFuture<'static, ()>;
+}
+
+/// The default GhostTimer, backed by a dedicated background thread,
+/// so it works with any executor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GhostThreadTimer;
+
+impl GhostTimer for GhostThreadTimer {
+    fn sleep(&self, dur: Duration) -> futures::future::BoxFuture<'static, ()> {
+        Box::pin(Delay::new(dur))
+    }
+}
+
+/// A GhostTimer backed by `tokio::time`.
+/// Must be used from within a tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GhostTokioTimer;
+
+#[cfg(feature = "tokio")]
+impl GhostTimer for GhostTokioTimer {
+    fn sleep(&self, dur: Duration) -> futures::future::BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(dur))
+    }
+}
+
+/// A GhostTimer backed by `async_std::task::sleep`.
+#[cfg(feature = "async-std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GhostAsyncStdTimer;
+
+#[cfg(feature = "async-std")]
+impl GhostTimer for GhostAsyncStdTimer {
+    fn sleep(&self, dur: Duration) -> futures::future::BoxFuture<'static, ()> {
+        Box::pin(async_std::task::sleep(dur))
+    }
+}
+
+/// Handle to a timer created by `GhostActor::invoke_after()` or
+/// `GhostActor::invoke_every()`. Dropping the handle does not cancel the
+/// timer, use `cancel()`. Timers stop automatically when the actor
+/// shuts down.
+#[derive(Debug, Clone)]
+pub struct GhostTimerHandle(pub(crate) futures::future::AbortHandle);
+
+impl GhostTimerHandle {
+    /// Stop the timer. Invocations it already queued are still processed.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    /// Returns `true` if `cancel()` has been called.
+    pub fn is_canceled(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// Resolve to the output of `fut`, or to a `GhostErrorKind::Timeout`
+/// error if `dur` elapses first.
+pub(crate) async fn timeout<F>(
+    timer: &dyn GhostTimer,
+    dur: Duration,
+    fut: F,
+) -> Result<F::Output, GhostError>
+where
+    F: std::future::Future,
+{
+    futures::pin_mut!(fut);
+    match futures::future::select(fut, timer.sleep(dur)).await {
+        futures::future::Either::Left((r, _)) => Ok(r),
+        futures::future::Either::Right(_) => {
+            Err(GhostErrorKind::Timeout.into())
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+struct Slot<V> {
+    value: Option<V>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Create a channel that only retains the latest value sent.
+pub(crate) fn channel<V>() -> (WatchSender<V>, GhostWatch<V>) {
+    let slot = Arc::new(Mutex::new(Slot {
+        value: None,
+        closed: false,
+        waker: None,
+    }));
+    (WatchSender(slot.clone()), GhostWatch(slot))
+}
+
+/// Sending side of a watch channel, held by the actor driver.
+pub(crate) struct WatchSender<V>(Arc<Mutex<Slot<V>>>);
+
+impl<V> WatchSender<V> {
+    /// Replace any value not yet received.
+    /// Returns `false` if the receiver has been dropped.
+    pub(crate) fn send(&self, value: V) -> bool {
+        let mut slot = self.0.lock().unwrap();
+        if slot.closed {
+            return false;
+        }
+        slot.value = Some(value);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    /// Returns `true` if the receiver has been dropped.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.0.lock().unwrap().closed
+    }
+}
+
+impl<V> Drop for WatchSender<V> {
+    fn drop(&mut self) {
+        let mut slot = self.0.lock().unwrap();
+        slot.closed = true;
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Stream of changes to a value selected from actor state,
+/// see `GhostActor::watch()`. If the value changes several times before
+/// it is polled, only the latest value is yielded. Ends when the actor
+/// shuts down.
+#[must_use = "streams do nothing unless polled"]
+pub struct GhostWatch<V>(Arc<Mutex<Slot<V>>>);
+
+impl<V> futures::stream::Stream for GhostWatch<V> {
+    type Item = V;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut slot = self.0.lock().unwrap();
+        if let Some(value) = slot.value.take() {
+            return Poll::Ready(Some(value));
+        }
+        if slot.closed {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<V> Drop for GhostWatch<V> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().closed = true;
+    }
+}
+
+impl<V> std::fmt::Debug for GhostWatch<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GhostWatch").finish()
+    }
+}